@@ -0,0 +1,135 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+pub const CONTAINER_PARAMS_VERSION: u8 = 1;
+
+// BYTES PASSED TO THE SWITCHBOARD FUNCTION
+// [0]: version | [1..]: borsh encoded ContainerParams
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ContainerParams {
+    /// Program the function sends trigger_update to.
+    pub program_id: Pubkey,
+    /// MintManager PDA.
+    pub mint_manager: Pubkey,
+    /// Token-2022 mint.
+    pub mint: Pubkey,
+    /// Switchboard request escrow.
+    pub escrow: Pubkey,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParamsError {
+    Empty,
+    UnsupportedVersion(u8),
+    InvalidEncoding(String),
+    MissingField(&'static str),
+}
+
+impl std::fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamsError::Empty => write!(f, "container params are empty"),
+            ParamsError::UnsupportedVersion(version) => {
+                write!(f, "unsupported container params version {}", version)
+            }
+            ParamsError::InvalidEncoding(err) => write!(f, "invalid container params: {}", err),
+            ParamsError::MissingField(field) => write!(f, "{} cannot be undefined", field),
+        }
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+impl ContainerParams {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![CONTAINER_PARAMS_VERSION];
+        // Only fixed size fields, writing to a Vec cannot fail
+        self.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, ParamsError> {
+        let (version, data) = bytes.split_first().ok_or(ParamsError::Empty)?;
+        let params = match *version {
+            CONTAINER_PARAMS_VERSION => Self::try_from_slice(data)
+                .map_err(|err| ParamsError::InvalidEncoding(err.to_string()))?,
+            version => return Err(ParamsError::UnsupportedVersion(version)),
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    pub fn validate(&self) -> Result<(), ParamsError> {
+        let fields = [
+            ("program_id", self.program_id),
+            ("mint_manager", self.mint_manager),
+            ("mint", self.mint),
+            ("escrow", self.escrow),
+        ];
+        match fields.iter().find(|(_, key)| *key == Pubkey::default()) {
+            Some((field, _)) => Err(ParamsError::MissingField(field)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> ContainerParams {
+        ContainerParams {
+            program_id: Pubkey::new_unique(),
+            mint_manager: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            escrow: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_params_roundtrip() {
+        let params = params();
+        let bytes = params.encode();
+
+        assert_eq!(bytes[0], CONTAINER_PARAMS_VERSION);
+        assert_eq!(bytes.len(), 1 + 4 * 32);
+        assert_eq!(ContainerParams::decode(&bytes).unwrap(), params);
+    }
+
+    #[test]
+    fn test_params_decode_empty() {
+        assert_eq!(ContainerParams::decode(&[]), Err(ParamsError::Empty));
+    }
+
+    #[test]
+    fn test_params_decode_unsupported_version() {
+        let mut bytes = params().encode();
+        bytes[0] = CONTAINER_PARAMS_VERSION + 1;
+
+        assert_eq!(
+            ContainerParams::decode(&bytes),
+            Err(ParamsError::UnsupportedVersion(CONTAINER_PARAMS_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn test_params_decode_truncated() {
+        let bytes = params().encode();
+
+        assert!(matches!(
+            ContainerParams::decode(&bytes[..bytes.len() - 1]),
+            Err(ParamsError::InvalidEncoding(_))
+        ));
+    }
+
+    #[test]
+    fn test_params_decode_missing_field() {
+        let mut params = params();
+        params.escrow = Pubkey::default();
+
+        assert_eq!(
+            ContainerParams::decode(&params.encode()),
+            Err(ParamsError::MissingField("escrow"))
+        );
+    }
+}
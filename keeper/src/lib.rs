@@ -0,0 +1,296 @@
+use anchor_lang::AccountDeserialize;
+use anyhow::{Context, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeAmount, AccountType, BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::Account,
+};
+
+use l2_client::MintManager;
+
+/// Sources per collect transaction, keeps a legacy transaction under the 1232 byte packet limit.
+pub const MAX_SOURCES_PER_TX: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithheldAccount {
+    pub address: Pubkey,
+    pub withheld_amount: u64,
+}
+
+/// Withheld fee amount of a Token-2022 account, zero if it has no TransferFeeAmount extension.
+pub fn withheld_amount(data: &[u8]) -> Result<u64> {
+    let account = StateWithExtensions::<Account>::unpack(data)?;
+    Ok(account
+        .get_extension::<TransferFeeAmount>()
+        .map(|extension| u64::from(extension.withheld_amount))
+        .unwrap_or_default())
+}
+
+/// Token accounts of the mint holding at least `min_withheld`, largest first.
+pub fn find_withheld_accounts(
+    rpc: &RpcClient,
+    mint: &Pubkey,
+    min_withheld: u64,
+) -> Result<Vec<WithheldAccount>> {
+    // Mint sits at offset 0, the account type byte right after the base account
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, mint.as_ref())),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                Account::LEN,
+                &[AccountType::Account as u8],
+            )),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(rpc.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        with_context: None,
+    };
+    let accounts = rpc
+        .get_program_accounts_with_config(&spl_token_2022::ID, config)
+        .context("failed to scan token accounts")?;
+
+    let mut withheld: Vec<WithheldAccount> = accounts
+        .into_iter()
+        .filter_map(|(address, account)| {
+            let withheld_amount = withheld_amount(&account.data).ok()?;
+            Some(WithheldAccount {
+                address,
+                withheld_amount,
+            })
+        })
+        .filter(|account| account.withheld_amount > 0 && account.withheld_amount >= min_withheld)
+        .collect();
+    withheld.sort_by_key(|account| std::cmp::Reverse(account.withheld_amount));
+    Ok(withheld)
+}
+
+pub fn fetch_mint_manager(rpc: &RpcClient, mint: &Pubkey) -> Result<MintManager> {
+    let address = l2_client::mint_manager(mint).0;
+    let data = rpc
+        .get_account_data(&address)
+        .with_context(|| format!("mint manager {} not found", address))?;
+    MintManager::try_deserialize(&mut data.as_slice())
+        .with_context(|| format!("failed to deserialize mint manager {}", address))
+}
+
+/// Treasury and rewards vault to pass to a collect, unset ones are stored as the default pubkey.
+pub fn collect_destinations(mint_manager: &MintManager) -> (Option<Pubkey>, Option<Pubkey>) {
+    let treasury = Some(mint_manager.treasury).filter(|key| *key != Pubkey::default());
+    let rewards_vault = Some(mint_manager.rewards_vault).filter(|key| *key != Pubkey::default());
+    (treasury, rewards_vault)
+}
+
+/// One collect_and_burn_from_account per batch of sources.
+pub fn build_collect_instructions(
+    mint_manager: &MintManager,
+    sources: &[WithheldAccount],
+    batch_size: usize,
+) -> Vec<Instruction> {
+    let (treasury, rewards_vault) = collect_destinations(mint_manager);
+
+    let addresses: Vec<Pubkey> = sources.iter().map(|source| source.address).collect();
+    addresses
+        .chunks(batch_size.clamp(1, MAX_SOURCES_PER_TX))
+        .map(|batch| {
+            l2_client::collect_and_burn_from_account(
+                &mint_manager.mint,
+                batch,
+                treasury,
+                rewards_vault,
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug, Default)]
+pub struct Sweep {
+    pub accounts: Vec<WithheldAccount>,
+    pub instructions: Vec<Instruction>,
+    pub signatures: Vec<Signature>,
+}
+
+impl Sweep {
+    pub fn total_withheld(&self) -> u64 {
+        self.accounts.iter().fold(0u64, |total, account| {
+            total.saturating_add(account.withheld_amount)
+        })
+    }
+}
+
+pub struct Keeper {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+    pub mint: Pubkey,
+    pub batch_size: usize,
+    pub min_withheld: u64,
+    pub dry_run: bool,
+}
+
+impl Keeper {
+    /// Scans once and submits every batch, a failed batch does not stop the rest.
+    pub fn run_once(&self) -> Result<Sweep> {
+        let mint_manager = fetch_mint_manager(&self.rpc, &self.mint)?;
+        let accounts = find_withheld_accounts(&self.rpc, &self.mint, self.min_withheld)?;
+        let instructions = build_collect_instructions(&mint_manager, &accounts, self.batch_size);
+
+        let mut sweep = Sweep {
+            accounts,
+            instructions,
+            signatures: vec![],
+        };
+        if self.dry_run {
+            return Ok(sweep);
+        }
+
+        for ix in sweep.instructions.iter() {
+            match self.send(ix.clone()) {
+                Ok(signature) => sweep.signatures.push(signature),
+                Err(err) => eprintln!("collect transaction failed: {:?}", err),
+            }
+        }
+        Ok(sweep)
+    }
+
+    fn send(&self, ix: Instruction) -> Result<Signature> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            blockhash,
+        );
+        Ok(self.rpc.send_and_confirm_transaction(&tx)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token_2022::{
+        extension::{ExtensionType, StateWithExtensionsMut},
+        state::AccountState,
+    };
+
+    fn token_account(mint: &Pubkey, withheld: u64) -> Vec<u8> {
+        let len = ExtensionType::try_calculate_account_len::<Account>(&[
+            ExtensionType::TransferFeeAmount,
+        ])
+        .unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+        state.base = Account {
+            mint: *mint,
+            owner: Pubkey::new_unique(),
+            state: AccountState::Initialized,
+            ..Account::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        state
+            .init_extension::<TransferFeeAmount>(false)
+            .unwrap()
+            .withheld_amount = withheld.into();
+        data
+    }
+
+    fn mint_manager(mint: Pubkey) -> MintManager {
+        MintManager {
+            mint,
+            ..Default::default()
+        }
+    }
+
+    fn withheld(count: usize) -> Vec<WithheldAccount> {
+        (0..count)
+            .map(|i| WithheldAccount {
+                address: Pubkey::new_unique(),
+                withheld_amount: i as u64 + 1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_withheld_amount() {
+        let mint = Pubkey::new_unique();
+        assert_eq!(withheld_amount(&token_account(&mint, 42)).unwrap(), 42);
+        assert_eq!(withheld_amount(&token_account(&mint, 0)).unwrap(), 0);
+        assert!(withheld_amount(&[0u8; 10]).is_err());
+    }
+
+    #[test]
+    fn test_account_type_filter_offset() {
+        let data = token_account(&Pubkey::new_unique(), 1);
+        assert_eq!(data[Account::LEN], AccountType::Account as u8);
+    }
+
+    #[test]
+    fn test_build_collect_instructions_batches() {
+        let mint = Pubkey::new_unique();
+        let sources = withheld(45);
+
+        let ixs = build_collect_instructions(&mint_manager(mint), &sources, 100);
+        assert_eq!(ixs.len(), 3);
+
+        // Sources are appended after the fixed collect accounts
+        let fixed = ixs[2].accounts.len() - 5;
+        assert_eq!(ixs[0].accounts.len() - fixed, MAX_SOURCES_PER_TX);
+        assert_eq!(ixs[2].accounts[fixed].pubkey, sources[40].address);
+
+        assert_eq!(
+            build_collect_instructions(&mint_manager(mint), &sources, 0).len(),
+            45
+        );
+        assert!(build_collect_instructions(&mint_manager(mint), &[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_build_collect_instructions_passes_destinations() {
+        let mint = Pubkey::new_unique();
+        let mut manager = mint_manager(mint);
+        manager.treasury = Pubkey::new_unique();
+
+        let ix = &build_collect_instructions(&manager, &withheld(1), 10)[0];
+        assert_eq!(ix.accounts[3].pubkey, manager.treasury);
+        // Unset rewards vault is left out, anchor passes the program id instead
+        assert_eq!(ix.accounts[4].pubkey, l2_client::l2::ID);
+    }
+
+    #[test]
+    fn test_full_batch_fits_in_packet() {
+        let payer = Keypair::new();
+        let mut manager = mint_manager(Pubkey::new_unique());
+        manager.treasury = Pubkey::new_unique();
+        manager.rewards_vault = Pubkey::new_unique();
+
+        let ix = build_collect_instructions(&manager, &withheld(MAX_SOURCES_PER_TX), 100).remove(0);
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&payer.pubkey()),
+            &[&payer],
+            solana_sdk::hash::Hash::default(),
+        );
+        let size = bincode::serialize(&tx).unwrap().len();
+        assert!(
+            size <= solana_sdk::packet::PACKET_DATA_SIZE,
+            "{} bytes",
+            size
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::{str::FromStr, thread, time::Duration};
+
+use anyhow::{anyhow, Result};
+use clap::{Arg, Command};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::read_keypair_file,
+};
+
+use keeper::{Keeper, MAX_SOURCES_PER_TX};
+
+fn main() -> Result<()> {
+    let max_sources = MAX_SOURCES_PER_TX.to_string();
+    let matches = Command::new("keeper")
+        .about("Collects withheld transfer fees of an l2 managed mint")
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .takes_value(true)
+                .default_value("http://127.0.0.1:8899"),
+        )
+        .arg(
+            Arg::new("keypair")
+                .long("keypair")
+                .takes_value(true)
+                .required(true)
+                .help("Fee payer keypair file"),
+        )
+        .arg(
+            Arg::new("mint")
+                .long("mint")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::new("batch-size")
+                .long("batch-size")
+                .takes_value(true)
+                .default_value(&max_sources)
+                .help("Source accounts per transaction, capped at the packet limit"),
+        )
+        .arg(
+            Arg::new("min-withheld")
+                .long("min-withheld")
+                .takes_value(true)
+                .default_value("1")
+                .help("Skip accounts withholding less than this"),
+        )
+        .arg(
+            Arg::new("interval-secs")
+                .long("interval-secs")
+                .takes_value(true)
+                .default_value("3600"),
+        )
+        .arg(Arg::new("once").long("once").help("Sweep once and exit"))
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Print the collections without sending them"),
+        )
+        .get_matches();
+
+    let keypair_path = matches.value_of("keypair").unwrap();
+    let keeper = Keeper {
+        rpc: RpcClient::new_with_commitment(
+            matches.value_of("url").unwrap().to_string(),
+            CommitmentConfig::confirmed(),
+        ),
+        payer: read_keypair_file(keypair_path)
+            .map_err(|err| anyhow!("failed to read keypair {}: {}", keypair_path, err))?,
+        mint: Pubkey::from_str(matches.value_of("mint").unwrap())?,
+        batch_size: matches.value_of_t("batch-size")?,
+        min_withheld: matches.value_of_t("min-withheld")?,
+        dry_run: matches.is_present("dry-run"),
+    };
+    let interval = Duration::from_secs(matches.value_of_t("interval-secs")?);
+
+    loop {
+        match keeper.run_once() {
+            Ok(sweep) => {
+                println!(
+                    "found {} accounts withholding {} in {} transactions",
+                    sweep.accounts.len(),
+                    sweep.total_withheld(),
+                    sweep.instructions.len()
+                );
+                if keeper.dry_run {
+                    for ix in sweep.instructions.iter() {
+                        println!("{:?}", ix);
+                    }
+                }
+                for signature in sweep.signatures.iter() {
+                    println!("collected: {}", signature);
+                }
+            }
+            Err(err) => eprintln!("sweep failed: {:?}", err),
+        }
+
+        if matches.is_present("once") {
+            return Ok(());
+        }
+        thread::sleep(interval);
+    }
+}
@@ -0,0 +1,170 @@
+// Needs a running `solana-test-validator`, run with `cargo test -p keeper -- --ignored`
+use std::{thread, time::Duration};
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use spl_token_2022::{
+    extension::{transfer_fee, ExtensionType},
+    instruction,
+    state::{Account, Mint},
+};
+
+use keeper::find_withheld_accounts;
+
+const URL: &str = "http://127.0.0.1:8899";
+const DECIMALS: u8 = 6;
+
+fn send(rpc: &RpcClient, payer: &Keypair, ixs: &[Instruction], signers: &[&Keypair]) {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        ixs,
+        Some(&payer.pubkey()),
+        &all_signers,
+        rpc.get_latest_blockhash().unwrap(),
+    );
+    rpc.send_and_confirm_transaction(&tx).unwrap();
+}
+
+fn airdrop(rpc: &RpcClient, to: &Pubkey) {
+    let signature = rpc.request_airdrop(to, 10 * LAMPORTS_PER_SOL).unwrap();
+    while !rpc.confirm_transaction(&signature).unwrap() {
+        thread::sleep(Duration::from_millis(200));
+    }
+}
+
+fn create_transfer_fee_mint(rpc: &RpcClient, payer: &Keypair, fee_bp: u16) -> Pubkey {
+    let mint = Keypair::new();
+    // The l2 mint manager would be the fee authority, the scan does not care who it is
+    let authority = Pubkey::new_unique();
+    let space =
+        ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+            .unwrap();
+    let ixs = [
+        system_instruction::create_account(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            rpc.get_minimum_balance_for_rent_exemption(space).unwrap(),
+            space as u64,
+            &spl_token_2022::ID,
+        ),
+        transfer_fee::instruction::initialize_transfer_fee_config(
+            &spl_token_2022::ID,
+            &mint.pubkey(),
+            Some(&authority),
+            Some(&authority),
+            fee_bp,
+            u64::MAX,
+        )
+        .unwrap(),
+        instruction::initialize_mint2(
+            &spl_token_2022::ID,
+            &mint.pubkey(),
+            &payer.pubkey(),
+            None,
+            DECIMALS,
+        )
+        .unwrap(),
+    ];
+    send(rpc, payer, &ixs, &[&mint]);
+    mint.pubkey()
+}
+
+fn create_token_account(rpc: &RpcClient, payer: &Keypair, mint: &Pubkey) -> Pubkey {
+    let account = Keypair::new();
+    let space =
+        ExtensionType::try_calculate_account_len::<Account>(&[ExtensionType::TransferFeeAmount])
+            .unwrap();
+    let ixs = [
+        system_instruction::create_account(
+            &payer.pubkey(),
+            &account.pubkey(),
+            rpc.get_minimum_balance_for_rent_exemption(space).unwrap(),
+            space as u64,
+            &spl_token_2022::ID,
+        ),
+        instruction::initialize_account3(
+            &spl_token_2022::ID,
+            &account.pubkey(),
+            mint,
+            &payer.pubkey(),
+        )
+        .unwrap(),
+    ];
+    send(rpc, payer, &ixs, &[&account]);
+    account.pubkey()
+}
+
+fn transfer(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    mint: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    amount: u64,
+) {
+    let ix = instruction::transfer_checked(
+        &spl_token_2022::ID,
+        from,
+        mint,
+        to,
+        &payer.pubkey(),
+        &[],
+        amount,
+        DECIMALS,
+    )
+    .unwrap();
+    send(rpc, payer, &[ix], &[]);
+}
+
+#[test]
+#[ignore]
+fn test_scan_finds_withheld_accounts() {
+    let rpc = RpcClient::new_with_commitment(URL.to_string(), CommitmentConfig::confirmed());
+    let payer = Keypair::new();
+    airdrop(&rpc, &payer.pubkey());
+
+    // 1% fee, the destination withholds it
+    let mint = create_transfer_fee_mint(&rpc, &payer, 100);
+    let source = create_token_account(&rpc, &payer, &mint);
+    let small = create_token_account(&rpc, &payer, &mint);
+    let large = create_token_account(&rpc, &payer, &mint);
+    let empty = create_token_account(&rpc, &payer, &mint);
+
+    let ix = instruction::mint_to(
+        &spl_token_2022::ID,
+        &mint,
+        &source,
+        &payer.pubkey(),
+        &[],
+        1_000_000,
+    )
+    .unwrap();
+    send(&rpc, &payer, &[ix], &[]);
+    transfer(&rpc, &payer, &mint, &source, &small, 10_000);
+    transfer(&rpc, &payer, &mint, &source, &large, 50_000);
+
+    let withheld = find_withheld_accounts(&rpc, &mint, 1).unwrap();
+    let found: Vec<(Pubkey, u64)> = withheld
+        .iter()
+        .map(|account| (account.address, account.withheld_amount))
+        .collect();
+    assert_eq!(found, vec![(large, 500), (small, 100)]);
+    assert!(withheld.iter().all(|account| account.address != empty));
+
+    let withheld = find_withheld_accounts(&rpc, &mint, 200).unwrap();
+    assert_eq!(withheld.len(), 1);
+    assert_eq!(withheld[0].address, large);
+
+    // What a dry run would print for the scanned accounts
+    let ix = l2_client::collect_and_burn_from_account(&mint, &[withheld[0].address], None, None);
+    assert_eq!(ix.accounts.last().unwrap().pubkey, large);
+}
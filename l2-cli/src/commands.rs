@@ -0,0 +1,402 @@
+use std::fmt::Write;
+
+use anchor_lang::AnchorDeserialize;
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_token_2022::extension::{transfer_fee, ExtensionType};
+
+use anchor_spl::token::spl_token;
+use keeper::{
+    build_collect_instructions, collect_destinations, fetch_mint_manager, find_withheld_accounts,
+};
+use l2_client::{EscrowRunway, FeeDestination, MintManager, SwitchboardAccounts};
+
+use crate::config::Config;
+
+pub struct Cli {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+    pub config: Config,
+}
+
+impl Cli {
+    fn send(&self, ixs: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.rpc.get_latest_blockhash()?,
+        );
+        Ok(self.rpc.send_and_confirm_transaction(&tx)?)
+    }
+
+    /// Transfer fee authorities go to the mint manager PDA so init_global accepts the mint.
+    pub fn create_mint(
+        &self,
+        mint: &Keypair,
+        decimals: u8,
+        fee_bp: u16,
+        max_fee: u64,
+    ) -> Result<Signature> {
+        let mint_manager = l2_client::mint_manager(&mint.pubkey()).0;
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+            ExtensionType::TransferFeeConfig,
+        ])?;
+        let ixs = [
+            system_instruction::create_account(
+                &self.payer.pubkey(),
+                &mint.pubkey(),
+                self.rpc.get_minimum_balance_for_rent_exemption(space)?,
+                space as u64,
+                &spl_token_2022::ID,
+            ),
+            transfer_fee::instruction::initialize_transfer_fee_config(
+                &spl_token_2022::ID,
+                &mint.pubkey(),
+                Some(&mint_manager),
+                Some(&mint_manager),
+                fee_bp,
+                max_fee,
+            )?,
+            spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::ID,
+                &mint.pubkey(),
+                &self.payer.pubkey(),
+                None,
+                decimals,
+            )?,
+        ];
+        self.send(&ixs, &[mint])
+    }
+
+    /// The payer has to be the mint authority, the request is created by init_global and signs.
+    /// Also creates the mint manager's Token-2022 ATA the collect instructions withdraw into.
+    pub fn init_global(
+        &self,
+        mint: &Pubkey,
+        request: &Keypair,
+        hour_to_next_update: u8,
+    ) -> Result<Signature> {
+        let switchboard = self.config.switchboard(request.pubkey())?;
+        let ixs = [
+            create_associated_token_account_idempotent(
+                &self.payer.pubkey(),
+                &l2_client::mint_manager(mint).0,
+                mint,
+                &spl_token_2022::ID,
+            ),
+            l2_client::init_global(mint, &self.payer.pubkey(), &switchboard, hour_to_next_update),
+        ];
+        self.send(&ixs, &[request])
+    }
+
+    /// Moves the manager to the function in the config, `request` is created and has to sign.
+    pub fn set_switchboard_function(&self, mint: &Pubkey, request: &Keypair) -> Result<Signature> {
+        let old = self.stored_switchboard(mint)?;
+        let new = self.config.switchboard(request.pubkey())?;
+        let ix = l2_client::set_switchboard_function(mint, &self.payer.pubkey(), &old, &new);
+        self.send(&[ix], &[request])
+    }
+
+    pub fn inspect(&self, mint: &Pubkey) -> Result<String> {
+        let mint_manager = fetch_mint_manager(&self.rpc, mint)?;
+        let mut out = String::new();
+        write_mint_manager(&mut out, &l2_client::mint_manager(mint).0, &mint_manager)?;
+        Ok(out)
+    }
+
+    /// Sweeps every token account with withheld fees, then whatever was harvested to the mint.
+    pub fn collect(&self, mint: &Pubkey, batch_size: usize) -> Result<Vec<Signature>> {
+        let mint_manager = fetch_mint_manager(&self.rpc, mint)?;
+        let sources = find_withheld_accounts(&self.rpc, mint, 1)?;
+
+        let (treasury, rewards_vault) = collect_destinations(&mint_manager);
+        let mut ixs = build_collect_instructions(&mint_manager, &sources, batch_size);
+        ixs.push(l2_client::collect_and_burn_from_mint(
+            mint,
+            treasury,
+            rewards_vault,
+        ));
+        ixs.into_iter().map(|ix| self.send(&[ix], &[])).collect()
+    }
+
+    /// Escrow recorded on the mint manager at init.
+    pub fn stored_escrow(&self, mint: &Pubkey) -> Result<Pubkey> {
+        Ok(fetch_mint_manager(&self.rpc, mint)?.switchboard_request_escrow)
+    }
+
+    fn stored_switchboard(&self, mint: &Pubkey) -> Result<SwitchboardAccounts> {
+        SwitchboardAccounts::from_mint_manager(&fetch_mint_manager(&self.rpc, mint)?)
+            .ok_or_else(|| anyhow!("mint manager has no Switchboard request recorded"))
+    }
+
+    /// Deposits into the escrow recorded on the mint manager, Switchboard pays the enclave from it.
+    pub fn deposit_escrow(&self, mint: &Pubkey, lamports: u64) -> Result<Signature> {
+        let switchboard = self.stored_switchboard(mint)?;
+        let ix = l2_client::deposit_escrow(mint, &self.payer.pubkey(), &switchboard, lamports);
+        self.send(&[ix], &[])
+    }
+
+    /// Wraps SOL into the escrow of any request, e.g. one not recorded on a manager yet.
+    pub fn fund_escrow(&self, escrow: &Pubkey, lamports: u64) -> Result<Signature> {
+        let ixs = [
+            system_instruction::transfer(&self.payer.pubkey(), escrow, lamports),
+            spl_token::instruction::sync_native(&spl_token::ID, escrow)?,
+        ];
+        self.send(&ixs, &[])
+    }
+
+    pub fn set_fee_override(
+        &self,
+        mint: &Pubkey,
+        transfer_fee_bp: u16,
+        duration_slots: u64,
+    ) -> Result<Signature> {
+        let ix = l2_client::set_fee_override(
+            mint,
+            &self.payer.pubkey(),
+            transfer_fee_bp,
+            duration_slots,
+        );
+        self.send(&[ix], &[])
+    }
+
+    pub fn set_fee_guardrails(
+        &self,
+        mint: &Pubkey,
+        fee_ceiling_bp: Option<u16>,
+        max_fee_delta_bp: Option<u16>,
+    ) -> Result<Signature> {
+        let ix = l2_client::set_fee_guardrails(
+            mint,
+            &self.payer.pubkey(),
+            fee_ceiling_bp,
+            max_fee_delta_bp,
+        );
+        self.send(&[ix], &[])
+    }
+
+    pub fn set_paused(&self, mint: &Pubkey, paused: bool) -> Result<Signature> {
+        let ix = l2_client::set_paused(mint, &self.payer.pubkey(), paused);
+        self.send(&[ix], &[])
+    }
+
+    pub fn set_bounty(&self, mint: &Pubkey, bounty: u64) -> Result<Signature> {
+        let ix = l2_client::set_bounty(mint, &self.payer.pubkey(), bounty);
+        self.send(&[ix], &[])
+    }
+
+    /// Simulates the escrow_runway view and decodes its return data.
+    pub fn escrow_runway(&self, mint: &Pubkey) -> Result<EscrowRunway> {
+        let switchboard = self.stored_switchboard(mint)?;
+        let tx = Transaction::new_signed_with_payer(
+            &[l2_client::escrow_runway(mint, &switchboard)],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.rpc.get_latest_blockhash()?,
+        );
+        let result = self.rpc.simulate_transaction(&tx)?.value;
+        if let Some(err) = result.err {
+            return Err(anyhow!("escrow_runway failed: {}", err));
+        }
+        let return_data = result
+            .return_data
+            .ok_or_else(|| anyhow!("escrow_runway returned no data"))?;
+        let data = STANDARD.decode(return_data.data.0)?;
+        Ok(EscrowRunway::deserialize(&mut data.as_slice())?)
+    }
+}
+
+fn format_optional(key: &Option<Pubkey>) -> String {
+    key.map(|key| key.to_string())
+        .unwrap_or_else(|| "none".to_string())
+}
+
+fn format_bp(bp: u16) -> String {
+    format!("{} bp ({:.2}%)", bp, bp as f64 / 100.0)
+}
+
+pub fn write_mint_manager(
+    out: &mut impl Write,
+    address: &Pubkey,
+    mint_manager: &MintManager,
+) -> std::fmt::Result {
+    writeln!(out, "Mint manager:          {}", address)?;
+    writeln!(out, "Mint:                  {}", mint_manager.mint)?;
+    writeln!(
+        out,
+        "Authority:             {}",
+        format_optional(&mint_manager.authority)
+    )?;
+    writeln!(
+        out,
+        "Pending authority:     {}",
+        format_optional(&mint_manager.pending_authority)
+    )?;
+
+    writeln!(out, "\nSchedule")?;
+    writeln!(
+        out,
+        "  Interval:            {}s",
+        mint_manager.update_interval_secs
+    )?;
+    writeln!(
+        out,
+        "  Interval bounds:     {}s - {}s",
+        mint_manager.min_interval_secs, mint_manager.max_interval_secs
+    )?;
+    writeln!(
+        out,
+        "  Next update:         {}",
+        mint_manager.next_update_ts
+    )?;
+    writeln!(
+        out,
+        "  Valid after slot:    {}",
+        mint_manager.valid_after_slot
+    )?;
+    writeln!(
+        out,
+        "  Late window:         {}s",
+        mint_manager.late_window_secs
+    )?;
+
+    writeln!(out, "\nSwitchboard")?;
+    writeln!(
+        out,
+        "  Function:            {}",
+        mint_manager.switchboard_function
+    )?;
+    writeln!(
+        out,
+        "  Attestation queue:   {}",
+        mint_manager.attestation_queue
+    )?;
+    writeln!(
+        out,
+        "  Program state:       {}",
+        mint_manager.attestation_program_state
+    )?;
+    writeln!(
+        out,
+        "  Request:             {}",
+        format_optional(&mint_manager.switchboard_request)
+    )?;
+    writeln!(
+        out,
+        "  Escrow:              {}",
+        mint_manager.switchboard_request_escrow
+    )?;
+    writeln!(out, "  Bounty:              {}", mint_manager.bounty)?;
+
+    writeln!(out, "\nFees")?;
+    writeln!(
+        out,
+        "  Scheduled fee:       {}",
+        format_bp(mint_manager.current_transfer_fee_bp)
+    )?;
+    writeln!(
+        out,
+        "  Older fee:           {}, max {}, until epoch {}",
+        format_bp(mint_manager.older_transfer_fee.transfer_fee_bp),
+        mint_manager.older_transfer_fee.max_fee,
+        mint_manager.newer_transfer_fee.epoch
+    )?;
+    writeln!(
+        out,
+        "  Newer fee:           {}, max {}, from epoch {}",
+        format_bp(mint_manager.newer_transfer_fee.transfer_fee_bp),
+        mint_manager.newer_transfer_fee.max_fee,
+        mint_manager.newer_transfer_fee.epoch
+    )?;
+    writeln!(out, "  Max fee:             {}", mint_manager.max_fee)?;
+    writeln!(
+        out,
+        "  Fee ceiling:         {}",
+        mint_manager
+            .fee_ceiling_bp
+            .map(format_bp)
+            .unwrap_or_else(|| "none".to_string())
+    )?;
+    writeln!(
+        out,
+        "  Max change:          {}",
+        mint_manager
+            .max_fee_delta_bp
+            .map(format_bp)
+            .unwrap_or_else(|| "none".to_string())
+    )?;
+    writeln!(out, "  Paused:              {}", mint_manager.paused)?;
+    writeln!(
+        out,
+        "  Override until slot: {}",
+        mint_manager.fee_override_until_slot
+    )?;
+    writeln!(out, "  Tiers:")?;
+    for tier in mint_manager.fee_tiers.iter() {
+        writeln!(out, "    {:<22} weight {}", format_bp(tier.bp), tier.weight)?;
+    }
+    let destination = match mint_manager.fee_destination {
+        FeeDestination::Burn => "burn".to_string(),
+        FeeDestination::Treasury => "treasury".to_string(),
+        FeeDestination::Split {
+            burn_bp,
+            treasury_bp,
+            rewards_bp,
+        } => format!(
+            "split burn {} / treasury {} / rewards {}",
+            burn_bp, treasury_bp, rewards_bp
+        ),
+    };
+    writeln!(out, "  Destination:         {}", destination)?;
+    writeln!(out, "  Treasury:            {}", mint_manager.treasury)?;
+    writeln!(out, "  Rewards vault:       {}", mint_manager.rewards_vault)?;
+
+    writeln!(out, "\nCollections")?;
+    writeln!(
+        out,
+        "  Count:               {}",
+        mint_manager.collection_count
+    )?;
+    writeln!(
+        out,
+        "  Last slot:           {}",
+        mint_manager.last_collection_slot
+    )?;
+    writeln!(
+        out,
+        "  Collected:           {}",
+        mint_manager.total_collected
+    )?;
+    writeln!(out, "  Burned:              {}", mint_manager.total_burned)?;
+    writeln!(
+        out,
+        "  To treasury:         {}",
+        mint_manager.total_to_treasury
+    )?;
+    writeln!(
+        out,
+        "  To rewards:          {}",
+        mint_manager.total_to_rewards
+    )
+}
+
+pub fn write_runway(out: &mut impl Write, runway: &EscrowRunway) -> std::fmt::Result {
+    writeln!(out, "Escrow balance:        {}", runway.balance)?;
+    writeln!(out, "Cost per run:          {}", runway.cost_per_run)?;
+    match runway.runs {
+        u64::MAX => writeln!(out, "Runs left:             unlimited"),
+        runs => writeln!(out, "Runs left:             {}", runs),
+    }
+}
@@ -0,0 +1,94 @@
+use std::{env, fs, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair},
+};
+
+use l2_client::SwitchboardAccounts;
+
+pub const DEFAULT_CONFIG_PATH: &str = "~/.config/l2-cli/config.toml";
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub rpc_url: String,
+    pub payer: String,
+    pub switchboard: Option<SwitchboardConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SwitchboardConfig {
+    pub attestation_queue: String,
+    pub function: String,
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Self> {
+        let path = expand_tilde(path);
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read config {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("invalid config {}", path.display()))
+    }
+
+    pub fn payer(&self) -> Result<Keypair> {
+        read_keypair(&self.payer)
+    }
+
+    /// The request is created per mint manager, only the queue and function are fixed.
+    pub fn switchboard(&self, request: Pubkey) -> Result<SwitchboardAccounts> {
+        let switchboard = self
+            .switchboard
+            .as_ref()
+            .ok_or_else(|| anyhow!("config is missing the [switchboard] section"))?;
+        Ok(SwitchboardAccounts {
+            attestation_queue: parse_pubkey(&switchboard.attestation_queue)?,
+            function: parse_pubkey(&switchboard.function)?,
+            request,
+        })
+    }
+}
+
+pub fn read_keypair(path: &str) -> Result<Keypair> {
+    let path = expand_tilde(path);
+    read_keypair_file(&path)
+        .map_err(|err| anyhow!("failed to read keypair {}: {}", path.display(), err))
+}
+
+pub fn parse_pubkey(value: &str) -> Result<Pubkey> {
+    Pubkey::from_str(value).with_context(|| format!("invalid pubkey {}", value))
+}
+
+fn expand_tilde(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_example_config() {
+        let config: Config = toml::from_str(include_str!("../config.example.toml")).unwrap();
+        assert_eq!(config.payer, "~/.config/solana/id.json");
+
+        let request = Pubkey::new_unique();
+        let switchboard = config.switchboard(request).unwrap();
+        assert_eq!(switchboard.request, request);
+        assert_eq!(
+            switchboard.attestation_queue.to_string(),
+            "CkvizjVnm2zA5Wuwan34NhVT3zFc7vqUyGnA6tuEF5aE"
+        );
+    }
+
+    #[test]
+    fn test_switchboard_section_is_optional() {
+        let config: Config =
+            toml::from_str("rpc_url = \"http://127.0.0.1:8899\"\npayer = \"id.json\"").unwrap();
+        assert!(config.switchboard(Pubkey::new_unique()).is_err());
+    }
+}
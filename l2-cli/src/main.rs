@@ -0,0 +1,310 @@
+use anyhow::Result;
+use clap::{Arg, ArgMatches, Command};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    native_token::sol_to_lamports,
+    signature::{Keypair, Signer},
+};
+
+mod commands;
+mod config;
+
+use commands::Cli;
+use config::{parse_pubkey, read_keypair, Config, DEFAULT_CONFIG_PATH};
+
+fn mint_arg() -> Arg<'static> {
+    Arg::new("mint")
+        .long("mint")
+        .takes_value(true)
+        .required(true)
+}
+
+/// Loads an existing keypair, or generates a fresh one when no path is given.
+fn keypair_or_new(matches: &ArgMatches, name: &str) -> Result<Keypair> {
+    match matches.value_of(name) {
+        Some(path) => read_keypair(path),
+        None => Ok(Keypair::new()),
+    }
+}
+
+fn main() -> Result<()> {
+    let matches = Command::new("l2-cli")
+        .about("Operates mint managers of the l2 program")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .takes_value(true)
+                .global(true)
+                .default_value(DEFAULT_CONFIG_PATH),
+        )
+        .subcommand(
+            Command::new("create-mint")
+                .about("Create a transfer fee mint with the mint manager as fee authority")
+                .arg(
+                    Arg::new("mint-keypair")
+                        .long("mint-keypair")
+                        .takes_value(true)
+                        .help("Defaults to a new keypair"),
+                )
+                .arg(
+                    Arg::new("decimals")
+                        .long("decimals")
+                        .takes_value(true)
+                        .default_value("9"),
+                )
+                .arg(
+                    Arg::new("fee-bp")
+                        .long("fee-bp")
+                        .takes_value(true)
+                        .default_value("0"),
+                )
+                .arg(
+                    Arg::new("max-fee")
+                        .long("max-fee")
+                        .takes_value(true)
+                        .default_value("18446744073709551615"),
+                ),
+        )
+        .subcommand(
+            Command::new("init-global")
+                .about("Create the mint manager, its Token-2022 ATA and its Switchboard request")
+                .arg(mint_arg())
+                .arg(
+                    Arg::new("request-keypair")
+                        .long("request-keypair")
+                        .takes_value(true)
+                        .help("Defaults to a new keypair"),
+                )
+                .arg(
+                    Arg::new("hours")
+                        .long("hours")
+                        .takes_value(true)
+                        .default_value("1")
+                        .help("Hours until the first fee update"),
+                ),
+        )
+        .subcommand(
+            Command::new("set-function")
+                .about("Close the request and recreate it against the function in the config")
+                .arg(mint_arg())
+                .arg(
+                    Arg::new("request-keypair")
+                        .long("request-keypair")
+                        .takes_value(true)
+                        .help("Defaults to a new keypair"),
+                ),
+        )
+        .subcommand(
+            Command::new("inspect")
+                .about("Print the mint manager")
+                .arg(mint_arg()),
+        )
+        .subcommand(
+            Command::new("collect")
+                .about("Collect and burn every withheld fee of the mint now")
+                .arg(mint_arg())
+                .arg(
+                    Arg::new("batch-size")
+                        .long("batch-size")
+                        .takes_value(true)
+                        .default_value("20"),
+                ),
+        )
+        .subcommand(
+            Command::new("fund-escrow")
+                .about("Wrap SOL into the Switchboard request escrow")
+                .arg(
+                    Arg::new("mint")
+                        .long("mint")
+                        .takes_value(true)
+                        .required_unless_present("request")
+                        .conflicts_with("request")
+                        .help("Deposit into the escrow recorded on the mint manager"),
+                )
+                .arg(
+                    Arg::new("request")
+                        .long("request")
+                        .takes_value(true)
+                        .help("Fund the escrow of this request instead"),
+                )
+                .arg(
+                    Arg::new("amount")
+                        .long("amount")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount in SOL"),
+                ),
+        )
+        .subcommand(
+            Command::new("override-fee")
+                .about("Schedule the fee, charged two epochs later, and suspend random updates for a number of slots")
+                .arg(mint_arg())
+                .arg(
+                    Arg::new("bp")
+                        .long("bp")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("slots")
+                        .long("slots")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("set-guardrails")
+                .about("Set the fee ceiling and max change per update, each only once")
+                .arg(mint_arg())
+                .arg(
+                    Arg::new("ceiling-bp")
+                        .long("ceiling-bp")
+                        .takes_value(true)
+                        .required_unless_present("max-change-bp"),
+                )
+                .arg(
+                    Arg::new("max-change-bp")
+                        .long("max-change-bp")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            Command::new("pause")
+                .about("Stop random fee updates, the request keeps being rescheduled")
+                .arg(mint_arg()),
+        )
+        .subcommand(
+            Command::new("unpause")
+                .about("Resume random fee updates")
+                .arg(mint_arg()),
+        )
+        .subcommand(
+            Command::new("set-bounty")
+                .about("Set the per-run bounty paid from the escrow to the oracle")
+                .arg(mint_arg())
+                .arg(
+                    Arg::new("amount")
+                        .long("amount")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount in SOL"),
+                ),
+        )
+        .subcommand(
+            Command::new("runway")
+                .about("Print how many more runs the request escrow pays for")
+                .arg(mint_arg()),
+        )
+        .get_matches();
+
+    let config = Config::load(matches.value_of("config").unwrap())?;
+    let cli = Cli {
+        rpc: RpcClient::new_with_commitment(config.rpc_url.clone(), CommitmentConfig::confirmed()),
+        payer: config.payer()?,
+        config,
+    };
+
+    match matches.subcommand() {
+        Some(("create-mint", args)) => {
+            let mint = keypair_or_new(args, "mint-keypair")?;
+            let signature = cli.create_mint(
+                &mint,
+                args.value_of_t("decimals")?,
+                args.value_of_t("fee-bp")?,
+                args.value_of_t("max-fee")?,
+            )?;
+            println!("mint: {}", mint.pubkey());
+            println!(
+                "mint manager: {}",
+                l2_client::mint_manager(&mint.pubkey()).0
+            );
+            println!("signature: {}", signature);
+        }
+        Some(("init-global", args)) => {
+            let mint = parse_pubkey(args.value_of("mint").unwrap())?;
+            let request = keypair_or_new(args, "request-keypair")?;
+            let signature = cli.init_global(&mint, &request, args.value_of_t("hours")?)?;
+            println!("request: {}", request.pubkey());
+            println!(
+                "escrow: {}",
+                l2_client::switchboard_request_escrow(&request.pubkey())
+            );
+            println!("signature: {}", signature);
+        }
+        Some(("set-function", args)) => {
+            let mint = parse_pubkey(args.value_of("mint").unwrap())?;
+            let request = keypair_or_new(args, "request-keypair")?;
+            let signature = cli.set_switchboard_function(&mint, &request)?;
+            println!("request: {}", request.pubkey());
+            println!(
+                "escrow: {}",
+                l2_client::switchboard_request_escrow(&request.pubkey())
+            );
+            println!("signature: {}", signature);
+        }
+        Some(("inspect", args)) => {
+            let mint = parse_pubkey(args.value_of("mint").unwrap())?;
+            print!("{}", cli.inspect(&mint)?);
+        }
+        Some(("collect", args)) => {
+            let mint = parse_pubkey(args.value_of("mint").unwrap())?;
+            for signature in cli.collect(&mint, args.value_of_t("batch-size")?)? {
+                println!("signature: {}", signature);
+            }
+        }
+        Some(("fund-escrow", args)) => {
+            let lamports = sol_to_lamports(args.value_of_t("amount")?);
+            let signature = match args.value_of("request") {
+                Some(request) => {
+                    let escrow = l2_client::switchboard_request_escrow(&parse_pubkey(request)?);
+                    println!("escrow: {}", escrow);
+                    cli.fund_escrow(&escrow, lamports)?
+                }
+                None => {
+                    let mint = parse_pubkey(args.value_of("mint").unwrap())?;
+                    println!("escrow: {}", cli.stored_escrow(&mint)?);
+                    cli.deposit_escrow(&mint, lamports)?
+                }
+            };
+            println!("signature: {}", signature);
+        }
+        Some(("override-fee", args)) => {
+            let mint = parse_pubkey(args.value_of("mint").unwrap())?;
+            let signature =
+                cli.set_fee_override(&mint, args.value_of_t("bp")?, args.value_of_t("slots")?)?;
+            println!("signature: {}", signature);
+        }
+        Some(("set-guardrails", args)) => {
+            let mint = parse_pubkey(args.value_of("mint").unwrap())?;
+            let ceiling_bp = args
+                .is_present("ceiling-bp")
+                .then(|| args.value_of_t("ceiling-bp"))
+                .transpose()?;
+            let max_change_bp = args
+                .is_present("max-change-bp")
+                .then(|| args.value_of_t("max-change-bp"))
+                .transpose()?;
+            let signature = cli.set_fee_guardrails(&mint, ceiling_bp, max_change_bp)?;
+            println!("signature: {}", signature);
+        }
+        Some((command @ ("pause" | "unpause"), args)) => {
+            let mint = parse_pubkey(args.value_of("mint").unwrap())?;
+            println!("signature: {}", cli.set_paused(&mint, command == "pause")?);
+        }
+        Some(("set-bounty", args)) => {
+            let mint = parse_pubkey(args.value_of("mint").unwrap())?;
+            let bounty = sol_to_lamports(args.value_of_t("amount")?);
+            println!("signature: {}", cli.set_bounty(&mint, bounty)?);
+        }
+        Some(("runway", args)) => {
+            let mint = parse_pubkey(args.value_of("mint").unwrap())?;
+            let mut out = String::new();
+            commands::write_runway(&mut out, &cli.escrow_runway(&mint)?)?;
+            print!("{}", out);
+        }
+        _ => unreachable!("subcommand is required"),
+    }
+    Ok(())
+}
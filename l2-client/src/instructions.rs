@@ -0,0 +1,451 @@
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::{instruction::Instruction, system_program},
+    InstructionData, ToAccountMetas,
+};
+use switchboard_solana::SWITCHBOARD_ATTESTATION_PROGRAM_ID;
+
+use crate::pda;
+use crate::{FeeDestination, FeeTier, MaxFee, MintManager, TriggerUpdateParams};
+
+/// Switchboard accounts backing a mint manager.
+#[derive(Clone, Copy, Debug)]
+pub struct SwitchboardAccounts {
+    pub attestation_queue: Pubkey,
+    pub function: Pubkey,
+    pub request: Pubkey,
+}
+
+impl SwitchboardAccounts {
+    /// Accounts stored on the manager, `None` before the request is recorded.
+    pub fn from_mint_manager(mint_manager: &MintManager) -> Option<Self> {
+        Some(Self {
+            attestation_queue: mint_manager.attestation_queue,
+            function: mint_manager.switchboard_function,
+            request: mint_manager.switchboard_request?,
+        })
+    }
+}
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: l2::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Extra token accounts passed through remaining accounts.
+fn with_remaining(mut ix: Instruction, remaining: &[Pubkey]) -> Instruction {
+    ix.accounts
+        .extend(remaining.iter().map(|key| AccountMeta::new(*key, false)));
+    ix
+}
+
+/// `payer` has to be the mint authority, the request signs as it is created inside init_global.
+pub fn init_global(
+    mint: &Pubkey,
+    payer: &Pubkey,
+    switchboard: &SwitchboardAccounts,
+    hour_to_next_update: u8,
+) -> Instruction {
+    build(
+        l2::accounts::InitGlobal {
+            mint: *mint,
+            mint_manager: pda::mint_manager(mint).0,
+            payer: *payer,
+            switchboard: SWITCHBOARD_ATTESTATION_PROGRAM_ID,
+            switchboard_state: pda::switchboard_state(),
+            switchboard_attestation_queue: switchboard.attestation_queue,
+            switchboard_function: switchboard.function,
+            switchboard_request: switchboard.request,
+            switchboard_request_escrow: pda::switchboard_request_escrow(&switchboard.request),
+            switchboard_mint: anchor_spl::token::spl_token::native_mint::ID,
+            token_program: anchor_spl::token::ID,
+            token_program_22: anchor_spl::token_2022::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        },
+        l2::instruction::InitGlobal {
+            hour_to_next_update,
+        },
+    )
+}
+
+pub fn trigger_update(
+    mint: &Pubkey,
+    enclave_signer: &Pubkey,
+    switchboard: &SwitchboardAccounts,
+    params: TriggerUpdateParams,
+) -> Instruction {
+    build(
+        l2::accounts::TriggerUpdate {
+            mint_manager: pda::mint_manager(mint).0,
+            mint: *mint,
+            enclave_signer: *enclave_signer,
+            switchboard: SWITCHBOARD_ATTESTATION_PROGRAM_ID,
+            switchboard_state: pda::switchboard_state(),
+            switchboard_attestation_queue: switchboard.attestation_queue,
+            switchboard_function: switchboard.function,
+            switchboard_request: switchboard.request,
+            switchboard_request_escrow: pda::switchboard_request_escrow(&switchboard.request),
+            switchboard_mint: anchor_spl::token::spl_token::native_mint::ID,
+            token_program: anchor_spl::token::ID,
+            token_program_22: anchor_spl::token_2022::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        },
+        l2::instruction::TriggerUpdate { params },
+    )
+}
+
+pub fn retrigger_update(
+    mint: &Pubkey,
+    payer: &Pubkey,
+    switchboard: &SwitchboardAccounts,
+) -> Instruction {
+    build(
+        l2::accounts::RetriggerUpdate {
+            mint_manager: pda::mint_manager(mint).0,
+            payer: *payer,
+            switchboard: SWITCHBOARD_ATTESTATION_PROGRAM_ID,
+            switchboard_state: pda::switchboard_state(),
+            switchboard_attestation_queue: switchboard.attestation_queue,
+            switchboard_function: switchboard.function,
+            switchboard_request: switchboard.request,
+            switchboard_request_escrow: pda::switchboard_request_escrow(&switchboard.request),
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+        },
+        l2::instruction::RetriggerUpdate {},
+    )
+}
+
+/// Closes the `old` request, the `new` request has to sign as it is created.
+pub fn set_switchboard_function(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    old: &SwitchboardAccounts,
+    new: &SwitchboardAccounts,
+) -> Instruction {
+    let mint_manager = pda::mint_manager(mint).0;
+    build(
+        l2::accounts::SetSwitchboardFunction {
+            mint_manager,
+            authority: *authority,
+            mint_manager_wsol: pda::mint_manager_wsol(&mint_manager),
+            switchboard: SWITCHBOARD_ATTESTATION_PROGRAM_ID,
+            switchboard_state: pda::switchboard_state(),
+            old_switchboard_function: old.function,
+            old_switchboard_request: old.request,
+            old_switchboard_request_escrow: pda::switchboard_request_escrow(&old.request),
+            switchboard_attestation_queue: new.attestation_queue,
+            switchboard_function: new.function,
+            switchboard_request: new.request,
+            switchboard_request_escrow: pda::switchboard_request_escrow(&new.request),
+            switchboard_mint: anchor_spl::token::spl_token::native_mint::ID,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        },
+        l2::instruction::SetSwitchboardFunction {},
+    )
+}
+
+fn collect_accounts(
+    mint: &Pubkey,
+    treasury: Option<Pubkey>,
+    rewards_vault: Option<Pubkey>,
+) -> l2::accounts::CollectAndBurn {
+    l2::accounts::CollectAndBurn {
+        mint_manager: pda::mint_manager(mint).0,
+        mint_manager_ata: pda::mint_manager_ata(mint),
+        mint: *mint,
+        treasury,
+        rewards_vault,
+        token_program: anchor_spl::token_2022::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+    }
+}
+
+/// Permissionless, wraps `amount` lamports of the depositor into the request escrow.
+pub fn deposit_escrow(
+    mint: &Pubkey,
+    depositor: &Pubkey,
+    switchboard: &SwitchboardAccounts,
+    amount: u64,
+) -> Instruction {
+    build(
+        l2::accounts::DepositEscrow {
+            mint_manager: pda::mint_manager(mint).0,
+            depositor: *depositor,
+            switchboard_request_escrow: pda::switchboard_request_escrow(&switchboard.request),
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+        },
+        l2::instruction::DepositEscrow { amount },
+    )
+}
+
+/// Read only, simulate it and decode the return data as `EscrowRunway`.
+pub fn escrow_runway(mint: &Pubkey, switchboard: &SwitchboardAccounts) -> Instruction {
+    build(
+        l2::accounts::ViewEscrowRunway {
+            mint_manager: pda::mint_manager(mint).0,
+            switchboard_attestation_queue: switchboard.attestation_queue,
+            switchboard_function: switchboard.function,
+            switchboard_request_escrow: pda::switchboard_request_escrow(&switchboard.request),
+        },
+        l2::instruction::EscrowRunway {},
+    )
+}
+
+/// Treasury and rewards vault are only needed when the fee destination sends to them.
+pub fn collect_and_burn_from_account(
+    mint: &Pubkey,
+    sources: &[Pubkey],
+    treasury: Option<Pubkey>,
+    rewards_vault: Option<Pubkey>,
+) -> Instruction {
+    with_remaining(
+        build(
+            collect_accounts(mint, treasury, rewards_vault),
+            l2::instruction::CollectAndBurnFromAccount {},
+        ),
+        sources,
+    )
+}
+
+pub fn collect_and_burn_from_mint(
+    mint: &Pubkey,
+    treasury: Option<Pubkey>,
+    rewards_vault: Option<Pubkey>,
+) -> Instruction {
+    build(
+        collect_accounts(mint, treasury, rewards_vault),
+        l2::instruction::CollectAndBurnFromMint {},
+    )
+}
+
+pub fn harvest_withheld_tokens_to_mint(mint: &Pubkey, sources: &[Pubkey]) -> Instruction {
+    with_remaining(
+        build(
+            l2::accounts::HarvestToMint {
+                mint_manager: pda::mint_manager(mint).0,
+                mint: *mint,
+                token_program: anchor_spl::token_2022::ID,
+            },
+            l2::instruction::HarvestWithheldTokensToMint {},
+        ),
+        sources,
+    )
+}
+
+fn update_accounts(mint: &Pubkey, authority: &Pubkey) -> l2::accounts::UpdateMintManager {
+    l2::accounts::UpdateMintManager {
+        mint_manager: pda::mint_manager(mint).0,
+        authority: *authority,
+    }
+}
+
+pub fn set_fee_tiers(mint: &Pubkey, authority: &Pubkey, fee_tiers: Vec<FeeTier>) -> Instruction {
+    build(
+        update_accounts(mint, authority),
+        l2::instruction::SetFeeTiers { fee_tiers },
+    )
+}
+
+pub fn set_bounty(mint: &Pubkey, authority: &Pubkey, bounty: u64) -> Instruction {
+    build(
+        update_accounts(mint, authority),
+        l2::instruction::SetBounty { bounty },
+    )
+}
+
+/// Each bound can only be set once, `None` leaves it untouched.
+pub fn set_fee_guardrails(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    fee_ceiling_bp: Option<u16>,
+    max_fee_delta_bp: Option<u16>,
+) -> Instruction {
+    build(
+        update_accounts(mint, authority),
+        l2::instruction::SetFeeGuardrails {
+            fee_ceiling_bp,
+            max_fee_delta_bp,
+        },
+    )
+}
+
+pub fn set_paused(mint: &Pubkey, authority: &Pubkey, paused: bool) -> Instruction {
+    build(
+        update_accounts(mint, authority),
+        l2::instruction::SetPaused { paused },
+    )
+}
+
+/// Schedules the fee on the mint, Token-2022 charges it from two epochs later on.
+/// Random updates resume after `duration_slots`.
+pub fn set_fee_override(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    transfer_fee_bp: u16,
+    duration_slots: u64,
+) -> Instruction {
+    build(
+        l2::accounts::SetFeeOverride {
+            mint_manager: pda::mint_manager(mint).0,
+            mint: *mint,
+            authority: *authority,
+            token_program_22: anchor_spl::token_2022::ID,
+        },
+        l2::instruction::SetFeeOverride {
+            transfer_fee_bp,
+            duration_slots,
+        },
+    )
+}
+
+pub fn set_max_fee(mint: &Pubkey, authority: &Pubkey, max_fee: MaxFee) -> Instruction {
+    build(
+        l2::accounts::UpdateMaxFee {
+            mint_manager: pda::mint_manager(mint).0,
+            mint: *mint,
+            authority: *authority,
+        },
+        l2::instruction::SetMaxFee { max_fee },
+    )
+}
+
+pub fn set_fee_destination(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    fee_destination: FeeDestination,
+    treasury: Option<Pubkey>,
+    rewards_vault: Option<Pubkey>,
+) -> Instruction {
+    build(
+        l2::accounts::UpdateFeeDestination {
+            mint_manager: pda::mint_manager(mint).0,
+            mint: *mint,
+            treasury,
+            rewards_vault,
+            authority: *authority,
+        },
+        l2::instruction::SetFeeDestination { fee_destination },
+    )
+}
+
+/// `max_interval_secs` has to be at least an epoch and at most 85h. Token-2022 charges a new
+/// fee two epochs after it is set, intervals under an epoch replace it before that.
+pub fn set_interval_bounds(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    min_interval_secs: i64,
+    max_interval_secs: i64,
+) -> Instruction {
+    build(
+        update_accounts(mint, authority),
+        l2::instruction::SetIntervalBounds {
+            min_interval_secs,
+            max_interval_secs,
+        },
+    )
+}
+
+pub fn set_late_window(mint: &Pubkey, authority: &Pubkey, late_window_secs: i64) -> Instruction {
+    build(
+        update_accounts(mint, authority),
+        l2::instruction::SetLateWindow { late_window_secs },
+    )
+}
+
+pub fn propose_authority(mint: &Pubkey, authority: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    build(
+        l2::accounts::UpdateAuthority {
+            mint_manager: pda::mint_manager(mint).0,
+            authority: *authority,
+        },
+        l2::instruction::ProposeAuthority {
+            new_authority: *new_authority,
+        },
+    )
+}
+
+pub fn accept_authority(mint: &Pubkey, pending_authority: &Pubkey) -> Instruction {
+    build(
+        l2::accounts::AcceptAuthority {
+            mint_manager: pda::mint_manager(mint).0,
+            pending_authority: *pending_authority,
+        },
+        l2::instruction::AcceptAuthority {},
+    )
+}
+
+pub fn renounce_authority(mint: &Pubkey, authority: &Pubkey) -> Instruction {
+    build(
+        l2::accounts::RemoveAuthority {
+            mint_manager: pda::mint_manager(mint).0,
+            authority: *authority,
+        },
+        l2::instruction::RenounceAuthority {},
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    fn switchboard() -> SwitchboardAccounts {
+        SwitchboardAccounts {
+            attestation_queue: Pubkey::new_unique(),
+            function: Pubkey::new_unique(),
+            request: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_trigger_update_layout() {
+        let mint = Pubkey::new_unique();
+        let enclave_signer = Pubkey::new_unique();
+        let switchboard = switchboard();
+        let params = TriggerUpdateParams {
+            version: l2::TRIGGER_UPDATE_VERSION,
+            randomness: [7; 32],
+        };
+
+        let ix = trigger_update(&mint, &enclave_signer, &switchboard, params);
+
+        assert_eq!(ix.program_id, l2::ID);
+        assert_eq!(ix.accounts.len(), 14);
+        assert_eq!(ix.accounts[0].pubkey, pda::mint_manager(&mint).0);
+        assert!(ix.accounts[0].is_writable);
+        assert_eq!(ix.accounts[1].pubkey, mint);
+        assert_eq!(ix.accounts[2].pubkey, enclave_signer);
+        assert!(ix.accounts[2].is_signer);
+        assert_eq!(ix.accounts[7].pubkey, switchboard.request);
+
+        assert_eq!(&ix.data[..8], &l2::instruction::TriggerUpdate::DISCRIMINATOR);
+        assert_eq!(ix.data[8], l2::TRIGGER_UPDATE_VERSION);
+        assert_eq!(&ix.data[9..], &[7; 32]);
+    }
+
+    #[test]
+    fn test_collect_and_burn_from_account_appends_sources() {
+        let mint = Pubkey::new_unique();
+        let sources = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let ix = collect_and_burn_from_account(&mint, &sources, None, None);
+
+        assert_eq!(ix.accounts[1].pubkey, pda::mint_manager_ata(&mint));
+        // Missing optional accounts are passed as the program id
+        assert_eq!(ix.accounts[3].pubkey, l2::ID);
+        assert_eq!(ix.accounts[4].pubkey, l2::ID);
+        let remaining = &ix.accounts[ix.accounts.len() - 2..];
+        assert_eq!(remaining[0].pubkey, sources[0]);
+        assert_eq!(remaining[1].pubkey, sources[1]);
+        assert!(remaining.iter().all(|meta| meta.is_writable && !meta.is_signer));
+    }
+}
@@ -0,0 +1,10 @@
+pub use l2;
+pub use l2::{
+    EpochFee, EscrowRunway, FeeDestination, FeeTier, MaxFee, MintManager, TriggerUpdateParams,
+};
+
+pub mod pda;
+pub use pda::*;
+
+pub mod instructions;
+pub use instructions::*;
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
+use switchboard_solana::AttestationProgramState;
+
+pub fn mint_manager(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[l2::MINT_MANAGER_SEED, mint.as_ref()], &l2::ID)
+}
+
+/// Token-2022 ATA the collect instructions withdraw into.
+pub fn mint_manager_ata(mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(
+        &mint_manager(mint).0,
+        mint,
+        &anchor_spl::token_2022::ID,
+    )
+}
+
+/// Wrapped SOL ATA paying for the Switchboard request.
+pub fn switchboard_request_escrow(request: &Pubkey) -> Pubkey {
+    get_associated_token_address(request, &anchor_spl::token::spl_token::native_mint::ID)
+}
+
+/// Wrapped SOL ATA receiving the old escrow while the request is rotated.
+pub fn mint_manager_wsol(mint_manager: &Pubkey) -> Pubkey {
+    get_associated_token_address(mint_manager, &anchor_spl::token::spl_token::native_mint::ID)
+}
+
+pub fn switchboard_state() -> Pubkey {
+    AttestationProgramState::get_pda()
+}
@@ -1,6 +1,9 @@
-use crate::{Global, RandomnessRequestError, GLOBAL_SEED};
+use crate::{MintManager, RandomnessRequestError, MINT_MANAGER_SEED};
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token_interface::Token2022};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Token2022},
+};
 use switchboard_solana::{
     AttestationProgramState, AttestationQueueAccountData, FunctionAccountData,
     FunctionRequestAccountData, Mint, Token, TokenAccount, SWITCHBOARD_ATTESTATION_PROGRAM_ID,
@@ -9,26 +12,31 @@ use switchboard_solana::{
 // 1. InitManagerState
 // 2. UpdateMintManager
 // 3. UpdateAuthority
-// 3. RemoveAuthority
-// 4. TriggerUpdate
-// 5. CallbackFunction
+// 4. AcceptAuthority
+// 5. RemoveAuthority
+// 6. TriggerUpdate
+// 7. RetriggerUpdate
+// 8. SetSwitchboardFunction
+// 9. DepositEscrow
+// 10. ViewEscrowRunway
+// 11. SetFeeOverride
 
 // 1. ONLY CALLED ONCE
 #[derive(Accounts)]
 pub struct InitGlobal<'info> {
     // RANDOMNESS PROGRAM ACCOUNTS
+    /// CHECK: validated in validate_mint
+    pub mint: AccountInfo<'info>,
     #[account(
-    init_if_needed,
-    seeds = [GLOBAL_SEED],
+    init,
+    seeds = [MINT_MANAGER_SEED, mint.key().as_ref()],
     bump,
     payer = payer,
-    space = 8 + std::mem::size_of::<Global>(),
+    space = 8 + MintManager::INIT_SPACE,
   )]
-    pub global: Box<Account<'info, Global>>,
-    /// CHECK:
-    // #[account(mint::token_program = token_2022::ID)]
-    pub mint: AccountInfo<'info>,
+    pub mint_manager: Box<Account<'info, MintManager>>,
 
+    // MINT AUTHORITY, BECOMES THE MANAGER AUTHORITY
     #[account(mut)]
     pub payer: Signer<'info>,
 
@@ -68,29 +76,119 @@ pub struct InitGlobal<'info> {
     pub system_program: Program<'info, System>,
 }
 
+// 2. AUTHORITY GATED SETTINGS
+#[derive(Accounts)]
+pub struct UpdateMintManager<'info> {
+    #[account(
+      mut, seeds = [MINT_MANAGER_SEED, mint_manager.mint.as_ref()], bump,
+      constraint = mint_manager.authority == Some(authority.key()) @ RandomnessRequestError::InvalidAuthority
+    )]
+    pub mint_manager: Account<'info, MintManager>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMaxFee<'info> {
+    #[account(
+      mut, seeds = [MINT_MANAGER_SEED, mint.key().as_ref()], bump,
+      constraint = mint_manager.authority == Some(authority.key()) @ RandomnessRequestError::InvalidAuthority
+    )]
+    pub mint_manager: Account<'info, MintManager>,
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeDestination<'info> {
+    #[account(
+      mut, seeds = [MINT_MANAGER_SEED, mint.key().as_ref()], bump,
+      constraint = mint_manager.authority == Some(authority.key()) @ RandomnessRequestError::InvalidAuthority
+    )]
+    pub mint_manager: Account<'info, MintManager>,
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+    #[account(token::mint = mint)]
+    pub treasury: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(token::mint = mint)]
+    pub rewards_vault: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub authority: Signer<'info>,
+}
+
+// 3. PROPOSE NEW AUTHORITY, TAKES EFFECT ON ACCEPT
+#[derive(Accounts)]
+pub struct UpdateAuthority<'info> {
+    #[account(
+      mut, seeds = [MINT_MANAGER_SEED, mint_manager.mint.as_ref()], bump,
+      constraint = mint_manager.authority == Some(authority.key()) @ RandomnessRequestError::InvalidAuthority
+    )]
+    pub mint_manager: Account<'info, MintManager>,
+    pub authority: Signer<'info>,
+}
+
+// 4. PENDING AUTHORITY TAKES OVER
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+      mut, seeds = [MINT_MANAGER_SEED, mint_manager.mint.as_ref()], bump,
+      constraint = mint_manager.pending_authority == Some(pending_authority.key()) @ RandomnessRequestError::InvalidPendingAuthority
+    )]
+    pub mint_manager: Account<'info, MintManager>,
+    pub pending_authority: Signer<'info>,
+}
+
+// 5. IRREVERSIBLE, NO SETTINGS CAN BE UPDATED AFTERWARDS
+#[derive(Accounts)]
+pub struct RemoveAuthority<'info> {
+    #[account(
+      mut, seeds = [MINT_MANAGER_SEED, mint_manager.mint.as_ref()], bump,
+      constraint = mint_manager.authority == Some(authority.key()) @ RandomnessRequestError::InvalidAuthority
+    )]
+    pub mint_manager: Account<'info, MintManager>,
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct CollectAndBurn<'info> {
-    #[account(mut, seeds = [GLOBAL_SEED], bump)]
-    pub global: Account<'info, Global>,
-    #[account(mut, associated_token::authority = global, associated_token::mint = mint)]
-    pub global_ata: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [MINT_MANAGER_SEED, mint.key().as_ref()], bump)]
+    pub mint_manager: Account<'info, MintManager>,
+    #[account(
+      mut,
+      associated_token::authority = mint_manager,
+      associated_token::mint = mint,
+      associated_token::token_program = token_program
+    )]
+    pub mint_manager_ata: InterfaceAccount<'info, token_interface::TokenAccount>,
 
-    pub mint: Account<'info, Mint>,
-    pub token_program: Program<'info, Token>,
+    #[account(mut, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+    // ONLY REQUIRED WHEN THE FEE DESTINATION SENDS TO THEM
+    #[account(mut, address = mint_manager.treasury)]
+    pub treasury: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(mut, address = mint_manager.rewards_vault)]
+    pub rewards_vault: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    pub token_program: Program<'info, Token2022>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
+// PERMISSIONLESS, SOURCE TOKEN ACCOUNTS PASSED IN REMAINING ACCOUNTS
+#[derive(Accounts)]
+pub struct HarvestToMint<'info> {
+    #[account(seeds = [MINT_MANAGER_SEED, mint.key().as_ref()], bump)]
+    pub mint_manager: Account<'info, MintManager>,
+    #[account(mut, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+    pub token_program: Program<'info, Token2022>,
+}
+
 #[derive(Accounts)]
 pub struct TriggerUpdate<'info> {
     // RANDOMNESS PROGRAM ACCOUNTS
     #[account(
-      mut, seeds = [GLOBAL_SEED], bump,
-      constraint = global.next_update_slot >= Clock::get()?.slot as u64 @ RandomnessRequestError::RequestNotReady
+      mut, seeds = [MINT_MANAGER_SEED, mint.key().as_ref()], bump,
+      constraint = Clock::get()?.unix_timestamp >= mint_manager.next_update_ts @ RandomnessRequestError::RequestNotReady
     )]
-    pub global: Box<Account<'info, Global>>,
-    /// CHECK:
-    #[account(mut)]
-    pub mint: Account<'info, Mint>,
+    pub mint_manager: Box<Account<'info, MintManager>>,
+    #[account(mut, mint::token_program = token_program_22)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
 
     pub enclave_signer: Signer<'info>,
 
@@ -99,10 +197,14 @@ pub struct TriggerUpdate<'info> {
     #[account(executable, address = SWITCHBOARD_ATTESTATION_PROGRAM_ID)]
     pub switchboard: AccountInfo<'info>,
     /// CHECK: validated by Switchboard CPI
+    #[account(address = mint_manager.attestation_program_state @ RandomnessRequestError::IncorrectSwitchboardState)]
     pub switchboard_state: AccountLoader<'info, AttestationProgramState>,
+    #[account(address = mint_manager.attestation_queue @ RandomnessRequestError::IncorrectAttestationQueue)]
     pub switchboard_attestation_queue: AccountLoader<'info, AttestationQueueAccountData>,
     /// CHECK: validated by Switchboard CPI
     #[account(
+      mut,
+      address = mint_manager.switchboard_function @ RandomnessRequestError::IncorrectSwitchboardFunction,
       constraint = switchboard_function.load()?.validate_request(
           &switchboard_request,
           &enclave_signer.to_account_info()
@@ -116,7 +218,114 @@ pub struct TriggerUpdate<'info> {
     //     &enclave_signer.to_account_info()
     //     )?
     //   )]
+    #[account(
+      mut,
+      constraint = mint_manager.switchboard_request == Some(switchboard_request.key()) @ RandomnessRequestError::IncorrectSwitchboardRequest
+    )]
     pub switchboard_request: Box<Account<'info, FunctionRequestAccountData>>,
+    #[account(mut, address = mint_manager.switchboard_request_escrow @ RandomnessRequestError::InvalidEscrow)]
+    pub switchboard_request_escrow: Box<Account<'info, TokenAccount>>,
+
+    // TOKEN ACCOUNTS
+    #[account(address = anchor_spl::token::spl_token::native_mint::ID)]
+    pub switchboard_mint: Box<Account<'info, Mint>>,
+    pub token_program: Program<'info, Token>,
+    pub token_program_22: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    // SYSTEM ACCOUNTS
+    pub system_program: Program<'info, System>,
+}
+
+// 7. PERMISSIONLESS, RE-TRIGGERS THE REQUEST ONCE THE ORACLE MISSED THE LATE WINDOW
+#[derive(Accounts)]
+pub struct RetriggerUpdate<'info> {
+    #[account(
+      mut, seeds = [MINT_MANAGER_SEED, mint_manager.mint.as_ref()], bump,
+      constraint = mint_manager.request_stale(&Clock::get()?) @ RandomnessRequestError::RequestNotStale
+    )]
+    pub mint_manager: Box<Account<'info, MintManager>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // SWITCHBOARD ACCOUNTS
+    /// CHECK:
+    #[account(executable, address = SWITCHBOARD_ATTESTATION_PROGRAM_ID)]
+    pub switchboard: AccountInfo<'info>,
+    /// CHECK: validated by Switchboard CPI
+    #[account(address = mint_manager.attestation_program_state @ RandomnessRequestError::IncorrectSwitchboardState)]
+    pub switchboard_state: AccountLoader<'info, AttestationProgramState>,
+    #[account(address = mint_manager.attestation_queue @ RandomnessRequestError::IncorrectAttestationQueue)]
+    pub switchboard_attestation_queue: AccountLoader<'info, AttestationQueueAccountData>,
+    /// CHECK: validated by Switchboard CPI
+    #[account(mut, address = mint_manager.switchboard_function @ RandomnessRequestError::IncorrectSwitchboardFunction)]
+    pub switchboard_function: AccountLoader<'info, FunctionAccountData>,
+    /// CHECK: validated by Switchboard CPI, only accepts the mint manager as authority
+    #[account(
+      mut,
+      constraint = mint_manager.switchboard_request == Some(switchboard_request.key()) @ RandomnessRequestError::IncorrectSwitchboardRequest
+    )]
+    pub switchboard_request: AccountInfo<'info>,
+    #[account(mut, address = mint_manager.switchboard_request_escrow @ RandomnessRequestError::InvalidEscrow)]
+    pub switchboard_request_escrow: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    // SYSTEM ACCOUNTS
+    pub system_program: Program<'info, System>,
+}
+
+// 8. AUTHORITY GATED, CLOSES THE REQUEST AND RECREATES IT AGAINST A NEW FUNCTION
+#[derive(Accounts)]
+pub struct SetSwitchboardFunction<'info> {
+    #[account(
+      mut, seeds = [MINT_MANAGER_SEED, mint_manager.mint.as_ref()], bump,
+      constraint = mint_manager.authority == Some(authority.key()) @ RandomnessRequestError::InvalidAuthority
+    )]
+    pub mint_manager: Box<Account<'info, MintManager>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    // RECEIVES THE OLD ESCROW BALANCE, CLOSED ONCE MOVED TO THE NEW ESCROW
+    #[account(
+      init_if_needed,
+      payer = authority,
+      associated_token::mint = switchboard_mint,
+      associated_token::authority = mint_manager,
+      associated_token::token_program = token_program
+    )]
+    pub mint_manager_wsol: Box<Account<'info, TokenAccount>>,
+
+    // SWITCHBOARD ACCOUNTS
+    /// CHECK:
+    #[account(executable, address = SWITCHBOARD_ATTESTATION_PROGRAM_ID)]
+    pub switchboard: AccountInfo<'info>,
+    /// CHECK: validated by Switchboard CPI
+    #[account(address = mint_manager.attestation_program_state @ RandomnessRequestError::IncorrectSwitchboardState)]
+    pub switchboard_state: AccountLoader<'info, AttestationProgramState>,
+    // OLD REQUEST, CLOSED
+    /// CHECK: validated by Switchboard CPI
+    #[account(mut, address = mint_manager.switchboard_function @ RandomnessRequestError::IncorrectSwitchboardFunction)]
+    pub old_switchboard_function: AccountInfo<'info>,
+    /// CHECK: validated by Switchboard CPI
+    #[account(
+      mut,
+      constraint = mint_manager.switchboard_request == Some(old_switchboard_request.key()) @ RandomnessRequestError::IncorrectSwitchboardRequest
+    )]
+    pub old_switchboard_request: AccountInfo<'info>,
+    /// CHECK: validated by Switchboard CPI
+    #[account(mut, address = mint_manager.switchboard_request_escrow @ RandomnessRequestError::InvalidEscrow)]
+    pub old_switchboard_request_escrow: AccountInfo<'info>,
+    // NEW REQUEST, CREATED AND TRIGGERED
+    pub switchboard_attestation_queue: AccountLoader<'info, AttestationQueueAccountData>,
+    /// CHECK: validated by Switchboard CPI
+    #[account(mut)]
+    pub switchboard_function: AccountLoader<'info, FunctionAccountData>,
+    /// CHECK: validated by Switchboard CPI
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = switchboard_request.data_len() == 0 && switchboard_request.lamports() == 0
+      )]
+    pub switchboard_request: AccountInfo<'info>,
     /// CHECK:
     #[account(
         mut,
@@ -129,8 +338,49 @@ pub struct TriggerUpdate<'info> {
     #[account(address = anchor_spl::token::spl_token::native_mint::ID)]
     pub switchboard_mint: Box<Account<'info, Mint>>,
     pub token_program: Program<'info, Token>,
-    pub token_program_22: Program<'info, Token2022>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     // SYSTEM ACCOUNTS
     pub system_program: Program<'info, System>,
 }
+
+// 9. PERMISSIONLESS, WRAPS SOL INTO THE REQUEST ESCROW
+#[derive(Accounts)]
+pub struct DepositEscrow<'info> {
+    #[account(seeds = [MINT_MANAGER_SEED, mint_manager.mint.as_ref()], bump)]
+    pub mint_manager: Box<Account<'info, MintManager>>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    #[account(mut, address = mint_manager.switchboard_request_escrow @ RandomnessRequestError::InvalidEscrow)]
+    pub switchboard_request_escrow: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    // SYSTEM ACCOUNTS
+    pub system_program: Program<'info, System>,
+}
+
+// 10. READ ONLY, RUNWAY RETURNED AS RETURN DATA
+#[derive(Accounts)]
+pub struct ViewEscrowRunway<'info> {
+    #[account(seeds = [MINT_MANAGER_SEED, mint_manager.mint.as_ref()], bump)]
+    pub mint_manager: Box<Account<'info, MintManager>>,
+    #[account(address = mint_manager.attestation_queue @ RandomnessRequestError::IncorrectAttestationQueue)]
+    pub switchboard_attestation_queue: AccountLoader<'info, AttestationQueueAccountData>,
+    #[account(address = mint_manager.switchboard_function @ RandomnessRequestError::IncorrectSwitchboardFunction)]
+    pub switchboard_function: AccountLoader<'info, FunctionAccountData>,
+    #[account(address = mint_manager.switchboard_request_escrow @ RandomnessRequestError::InvalidEscrow)]
+    pub switchboard_request_escrow: Box<Account<'info, TokenAccount>>,
+}
+
+// 11. AUTHORITY GATED, SCHEDULES THE FEE ON THE MINT, TOKEN22 CHARGES IT TWO EPOCHS LATER
+#[derive(Accounts)]
+pub struct SetFeeOverride<'info> {
+    #[account(
+      mut, seeds = [MINT_MANAGER_SEED, mint.key().as_ref()], bump,
+      constraint = mint_manager.authority == Some(authority.key()) @ RandomnessRequestError::InvalidAuthority
+    )]
+    pub mint_manager: Box<Account<'info, MintManager>>,
+    #[account(mut, mint::token_program = token_program_22)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    pub authority: Signer<'info>,
+    pub token_program_22: Program<'info, Token2022>,
+}
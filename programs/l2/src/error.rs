@@ -17,7 +17,7 @@ pub enum RandomnessRequestError {
     InvalidMrEnclave,
     #[msg("Failed to find a valid trading symbol for this price")]
     InvalidSymbol,
-    #[msg("FunctionAccount pubkey did not match program_state.function")]
+    #[msg("FunctionAccount pubkey did not match mint_manager.switchboard_function")]
     IncorrectSwitchboardFunction,
     #[msg("FunctionAccount pubkey did not match program_state.function")]
     InvalidSwitchboardFunction,
@@ -33,4 +33,58 @@ pub enum RandomnessRequestError {
     RequestNotReady,
     #[msg("Random result is out-of-bounds")]
     RandomResultOutOfBounds,
+    #[msg("Fee tiers must be 1-16 entries, each <= 10000 bp, with a non-zero total weight")]
+    InvalidFeeTiers,
+    #[msg("Max fee does not fit in u64 with the mint decimals")]
+    MaxFeeOverflow,
+    #[msg("Mint is not owned by the Token-2022 program")]
+    InvalidMintOwner,
+    #[msg("Mint data could not be unpacked")]
+    InvalidMint,
+    #[msg("Mint is missing the TransferFeeConfig extension")]
+    MissingTransferFeeConfig,
+    #[msg("Transfer fee config authority must be the mint manager")]
+    InvalidTransferFeeConfigAuthority,
+    #[msg("Withdraw withheld authority must be the mint manager")]
+    InvalidWithdrawWithheldAuthority,
+    #[msg("Fee split has to sum up to 10000 bp")]
+    InvalidFeeSplit,
+    #[msg("Fee destination needs a treasury token account")]
+    MissingTreasury,
+    #[msg("Fee destination needs a rewards vault token account")]
+    MissingRewardsVault,
+    #[msg("Request is still within its late window")]
+    RequestNotStale,
+    #[msg("Late window cannot be negative")]
+    InvalidLateWindow,
+    #[msg("Interval bounds need 0 < min_interval <= max_interval <= 85h")]
+    InvalidIntervalBounds,
+    #[msg("Unsupported trigger_update params version")]
+    UnsupportedParamsVersion,
+    #[msg("FunctionRequestAccount pubkey did not match mint_manager.switchboard_request")]
+    IncorrectSwitchboardRequest,
+    #[msg("AttestationProgramState pubkey did not match mint_manager.attestation_program_state")]
+    IncorrectSwitchboardState,
+    #[msg("AttestationQueue pubkey did not match mint_manager.attestation_queue")]
+    IncorrectAttestationQueue,
+    #[msg("Request escrow cannot cover the next run, top it up with deposit_escrow")]
+    InsufficientEscrow,
+    #[msg("Fee override has to be <= 10000 bp")]
+    InvalidFeeOverride,
+    #[msg("Fee guardrail is already set and cannot be changed")]
+    FeeGuardrailAlreadySet,
+    #[msg("Fee ceiling has to be <= 10000 bp and the max change per update > 0")]
+    InvalidFeeGuardrails,
+    #[msg("Fee is above the mint manager fee ceiling")]
+    FeeAboveCeiling,
+    #[msg("Fee change is larger than the max change per update")]
+    FeeChangeTooLarge,
+    #[msg("init_global has to be paid by the mint authority")]
+    InvalidMintAuthority,
+    #[msg("Next update timestamp overflowed")]
+    NextUpdateOverflow,
+    #[msg("Signer is not the pending authority")]
+    InvalidPendingAuthority,
+    #[msg("Max interval is shorter than an epoch, the random fee would never be charged")]
+    IntervalBelowEpoch,
 }
@@ -0,0 +1,156 @@
+use crate::*;
+
+#[event]
+pub struct GlobalInitialized {
+    pub mint_manager: Pubkey,
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub switchboard_function: Pubkey,
+    pub current_transfer_fee_bp: u16,
+    pub next_update_ts: i64,
+}
+
+#[event]
+pub struct FeeUpdated {
+    pub mint: Pubkey,
+    pub old_bp: u16,
+    pub new_bp: u16,
+    pub max_fee: u64,
+    pub randomness: [u8; 32],
+    pub next_update_ts: i64,
+}
+
+#[event]
+pub struct FeeUpdateSkipped {
+    pub mint: Pubkey,
+    pub paused: bool,
+    pub fee_override_until_slot: u64,
+    pub next_update_ts: i64,
+}
+
+#[event]
+pub struct FeeOverrideSet {
+    pub mint: Pubkey,
+    pub old_bp: u16,
+    pub new_bp: u16,
+    /// First epoch Token-2022 charges new_bp.
+    pub effective_epoch: u64,
+    /// First slot trigger_update picks a random fee again.
+    pub until_slot: u64,
+}
+
+#[event]
+pub struct FeeGuardrailsSet {
+    pub mint: Pubkey,
+    pub fee_ceiling_bp: Option<u16>,
+    pub max_fee_delta_bp: Option<u16>,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub mint: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct RequestRetriggered {
+    pub mint: Pubkey,
+    pub payer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwitchboardFunctionUpdated {
+    pub mint: Pubkey,
+    pub old_function: Pubkey,
+    pub function: Pubkey,
+    pub request: Pubkey,
+    pub escrow: Pubkey,
+    /// wSOL carried over from the old escrow.
+    pub escrow_amount: u64,
+}
+
+#[event]
+pub struct EscrowDeposited {
+    pub mint: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    /// Escrow balance after the deposit.
+    pub balance: u64,
+}
+
+#[event]
+pub struct BountyUpdated {
+    pub mint: Pubkey,
+    pub bounty: u64,
+}
+
+#[event]
+pub struct ScheduleUpdated {
+    pub mint: Pubkey,
+    pub min_interval_secs: i64,
+    pub max_interval_secs: i64,
+    pub late_window_secs: i64,
+}
+
+#[event]
+pub struct FeeTiersUpdated {
+    pub mint: Pubkey,
+    pub fee_tiers: Vec<FeeTier>,
+}
+
+#[event]
+pub struct MaxFeeUpdated {
+    pub mint: Pubkey,
+    pub max_fee: u64,
+}
+
+#[event]
+pub struct FeeDestinationUpdated {
+    pub mint: Pubkey,
+    pub fee_destination: FeeDestination,
+    pub treasury: Pubkey,
+    pub rewards_vault: Pubkey,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityAccepted {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityRenounced {
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct WithheldHarvested {
+    pub mint: Pubkey,
+    pub source_count: u32,
+}
+
+#[event]
+pub struct FeesCollected {
+    pub mint: Pubkey,
+    /// Withheld tokens withdrawn.
+    pub amount: u64,
+    /// Received by the treasury and rewards vault, net of the transfer fee.
+    pub to_treasury: u64,
+    pub to_rewards: u64,
+}
+
+#[event]
+pub struct TokensBurned {
+    pub mint: Pubkey,
+    /// Token account the tokens were burned from.
+    pub source: Pubkey,
+    pub amount: u64,
+}
@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{burn, Burn};
+use container_params::ContainerParams;
 use spl_token_2022::extension::transfer_fee::instruction::{
-    set_transfer_fee, withdraw_withheld_tokens_from_accounts, withdraw_withheld_tokens_from_mint,
+    self as transfer_fee_ix, withdraw_withheld_tokens_from_accounts,
+    withdraw_withheld_tokens_from_mint,
+};
+use switchboard_solana::{
+    invoke, invoke_signed, FunctionRequestClose, FunctionRequestInit, FunctionRequestTrigger,
 };
-use switchboard_solana::{invoke_signed, FunctionRequestInit, FunctionRequestTrigger};
 
 pub mod error;
 pub use error::*;
@@ -11,6 +14,9 @@ pub use error::*;
 pub mod context;
 pub use context::*;
 
+pub mod events;
+pub use events::*;
+
 pub mod state;
 pub use state::*;
 
@@ -18,7 +24,7 @@ pub mod utils;
 pub use utils::*;
 
 declare_id!("auULn3TunUFz5mvM1VSLUT184oAApgnEsLmqZrVyUAP");
-// WITHHELD_WITHDRAW & TRANSFER_FEE AUTHORITY REQUIRED TO BE GLOBAL PDA
+// WITHHELD_WITHDRAW & TRANSFER_FEE AUTHORITY REQUIRED TO BE MINT MANAGER PDA
 // MINT MANAGER AUTHORITY CONSTRAINT CHECK TO UPDATE
 
 #[program]
@@ -26,24 +32,28 @@ pub mod l2 {
 
     use super::*;
 
-    // INITIALIZE GLOBAL, MINT REQUIRE TRANSFER_FEE & MINT AUTHORITY
+    // INITIALIZE MINT MANAGER, MINT REQUIRE TRANSFER_FEE & MINT AUTHORITY
     pub fn init_global(ctx: Context<InitGlobal>, hour_to_next_update: u8) -> Result<()> {
-        // TODO: CHECK MINT
-        let global = &mut ctx.accounts.global;
-        let request_params = format!(
-            "PID={},MAX_VALUE={},GLOBAL={},MINT={},ESCROW={}",
-            crate::id(),
-            MAX_RAND_VALUE,
-            global.key(),
-            ctx.accounts.mint.key(),
-            ctx.accounts.switchboard_request_escrow.key(),
-        );
-        let container_params = request_params.into_bytes();
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        let mint_key = ctx.accounts.mint.key();
+        let transfer_fee_config = validate_mint(
+            &ctx.accounts.mint,
+            &mint_manager.key(),
+            &ctx.accounts.payer.key(),
+        )?;
+
+        let container_params = ContainerParams {
+            program_id: crate::id(),
+            mint_manager: mint_manager.key(),
+            mint: mint_key,
+            escrow: ctx.accounts.switchboard_request_escrow.key(),
+        }
+        .encode();
 
         // Create the Switchboard request account.
         let request_init_ctx = FunctionRequestInit {
             request: ctx.accounts.switchboard_request.clone(),
-            authority: global.to_account_info(), // AUTHORITY OVER REQUEST
+            authority: mint_manager.to_account_info(), // AUTHORITY OVER REQUEST
             function: ctx.accounts.switchboard_function.to_account_info(),
             function_authority: None,
             escrow: ctx.accounts.switchboard_request_escrow.clone(), // ESCROW ACCOUNT THAT WILL BE PAYING FOR FEES
@@ -69,15 +79,18 @@ pub mod l2 {
             None,
         )?;
 
-        let current_slot = Clock::get()?.slot as u64;
-        // let new_next_update = current_slot + HOURLY_SLOTS as u64 * hour_to_next_update as u64;
-        let new_next_update = current_slot + 5; // 10 Seconds
+        let clock = Clock::get()?;
+        let delay = SECONDS_PER_HOUR * hour_to_next_update as i64;
+        let new_next_update = clock
+            .unix_timestamp
+            .checked_add(delay)
+            .ok_or_else(|| error!(RandomnessRequestError::NextUpdateOverflow))?;
 
         // Then trigger it
-        // We do this in two steps so we can set the authority to our Lottery PDA
+        // We do this in two steps so we can set the authority to our MintManager PDA
         let trigger_ctx = FunctionRequestTrigger {
             request: ctx.accounts.switchboard_request.to_account_info(),
-            authority: global.to_account_info(),
+            authority: mint_manager.to_account_info(),
             escrow: ctx.accounts.switchboard_request_escrow.to_account_info(),
             function: ctx.accounts.switchboard_function.to_account_info(),
             state: ctx.accounts.switchboard_state.to_account_info(),
@@ -86,6 +99,7 @@ pub mod l2 {
             token_program: ctx.accounts.token_program.to_account_info(),
             system_program: ctx.accounts.system_program.to_account_info(),
         };
+        mint_manager.valid_after_slot = valid_after_slot(clock.slot, delay);
         trigger_ctx.invoke_signed(
             ctx.accounts.switchboard.clone(),
             // bounty - the amount of SOL to pay the Switchboard Function for executing the request
@@ -93,166 +107,733 @@ pub mod l2 {
             // slots_until_expiration - the number of slots until the request expires
             None,
             // valid_after_slot - the slot when the request can be executed
-            Some(new_next_update),
-            // Lottery PDA seeds
-            &[&[GLOBAL_SEED, &[ctx.bumps.global]]],
+            Some(mint_manager.valid_after_slot),
+            // MintManager PDA seeds
+            &[&[MINT_MANAGER_SEED, mint_key.as_ref(), &[ctx.bumps.mint_manager]]],
+        )?;
+
+        mint_manager.update_interval_secs = delay;
+        // EVERY DRAW AT LEAST AN EPOCH, SO THE FEE SET BY AN UPDATE GETS CHARGED
+        let epoch_schedule = EpochSchedule::get()?;
+        let min_interval_secs =
+            MintManager::min_effective_interval_secs(&epoch_schedule).min(MAX_INTERVAL_SECS);
+        MintManager::validate_interval_bounds(
+            min_interval_secs,
+            MAX_INTERVAL_SECS,
+            &epoch_schedule,
+        )?;
+        mint_manager.min_interval_secs = min_interval_secs;
+        mint_manager.max_interval_secs = MAX_INTERVAL_SECS;
+        mint_manager.next_update_ts = new_next_update;
+        mint_manager.late_window_secs = SECONDS_PER_HOUR;
+        mint_manager.mint = mint_key;
+        mint_manager.authority = Some(ctx.accounts.payer.key());
+        mint_manager.pending_authority = None;
+
+        mint_manager.switchboard_function = ctx.accounts.switchboard_function.key();
+        mint_manager.attestation_program_state = ctx.accounts.switchboard_state.key();
+        mint_manager.attestation_queue = ctx.accounts.switchboard_attestation_queue.key();
+        mint_manager.switchboard_request = Some(ctx.accounts.switchboard_request.key());
+        mint_manager.switchboard_request_escrow = ctx.accounts.switchboard_request_escrow.key();
+
+        // START FROM THE SCHEDULE ALREADY ON THE MINT, THE GUARDRAILS MEASURE FROM IT
+        mint_manager.older_transfer_fee = (&transfer_fee_config.older_transfer_fee).into();
+        mint_manager.newer_transfer_fee = (&transfer_fee_config.newer_transfer_fee).into();
+        mint_manager.current_transfer_fee_bp = mint_manager.newer_transfer_fee.transfer_fee_bp;
+        mint_manager.max_fee = mint_manager.newer_transfer_fee.max_fee;
+        mint_manager.fee_tiers = MintManager::default_fee_tiers();
+        mint_manager.fee_destination = FeeDestination::Burn;
+
+        emit!(GlobalInitialized {
+            mint_manager: mint_manager.key(),
+            mint: mint_key,
+            authority: ctx.accounts.payer.key(),
+            switchboard_function: mint_manager.switchboard_function,
+            current_transfer_fee_bp: mint_manager.current_transfer_fee_bp,
+            next_update_ts: new_next_update,
+        });
+
+        Ok(())
+    }
+
+    // REPLACES THE WHOLE TABLE, APPLIED FROM THE NEXT TRIGGER_UPDATE
+    pub fn set_fee_tiers(ctx: Context<UpdateMintManager>, fee_tiers: Vec<FeeTier>) -> Result<()> {
+        MintManager::validate_fee_tiers(&fee_tiers)?;
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        mint_manager.fee_tiers = fee_tiers;
+
+        emit!(FeeTiersUpdated {
+            mint: mint_manager.mint,
+            fee_tiers: mint_manager.fee_tiers.clone(),
+        });
+        Ok(())
+    }
+
+    // APPLIED ON THE MINT FROM THE NEXT TRIGGER_UPDATE
+    pub fn set_max_fee(ctx: Context<UpdateMaxFee>, max_fee: MaxFee) -> Result<()> {
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        mint_manager.max_fee = max_fee.to_amount(ctx.accounts.mint.decimals)?;
+
+        emit!(MaxFeeUpdated {
+            mint: mint_manager.mint,
+            max_fee: mint_manager.max_fee,
+        });
+        Ok(())
+    }
+
+    // APPLIED FROM THE NEXT COLLECT, TOKEN ACCOUNTS ONLY NEEDED FOR THE MODES USING THEM
+    pub fn set_fee_destination(
+        ctx: Context<UpdateFeeDestination>,
+        fee_destination: FeeDestination,
+    ) -> Result<()> {
+        fee_destination.validate()?;
+        let (sends_treasury, sends_rewards) = match fee_destination {
+            FeeDestination::Burn => (false, false),
+            FeeDestination::Treasury => (true, false),
+            FeeDestination::Split {
+                treasury_bp,
+                rewards_bp,
+                ..
+            } => (treasury_bp > 0, rewards_bp > 0),
+        };
+        let treasury = ctx.accounts.treasury.as_ref().map(|acc| acc.key());
+        let rewards_vault = ctx.accounts.rewards_vault.as_ref().map(|acc| acc.key());
+        require!(
+            !sends_treasury || treasury.is_some(),
+            RandomnessRequestError::MissingTreasury
+        );
+        require!(
+            !sends_rewards || rewards_vault.is_some(),
+            RandomnessRequestError::MissingRewardsVault
+        );
+
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        mint_manager.fee_destination = fee_destination;
+        mint_manager.treasury = treasury.unwrap_or_default();
+        mint_manager.rewards_vault = rewards_vault.unwrap_or_default();
+
+        emit!(FeeDestinationUpdated {
+            mint: mint_manager.mint,
+            fee_destination,
+            treasury: mint_manager.treasury,
+            rewards_vault: mint_manager.rewards_vault,
+        });
+        Ok(())
+    }
+
+    // APPLIED FROM THE NEXT TRIGGER_UPDATE
+    // TOKEN22 ONLY CHARGES A FEE FROM TWO EPOCHS AFTER IT IS SET AND EVERY UPDATE BEFORE THAT
+    // REPLACES IT. A MAX UNDER ONE EPOCH (~2 DAYS ON MAINNET) NEVER LETS THE RANDOM FEE APPLY AND
+    // IS REJECTED, DRAWS UNDER TWO EPOCHS ONLY APPLY SOME OF THE TIME
+    pub fn set_interval_bounds(
+        ctx: Context<UpdateMintManager>,
+        min_interval_secs: i64,
+        max_interval_secs: i64,
+    ) -> Result<()> {
+        MintManager::validate_interval_bounds(
+            min_interval_secs,
+            max_interval_secs,
+            &EpochSchedule::get()?,
         )?;
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        mint_manager.min_interval_secs = min_interval_secs;
+        mint_manager.max_interval_secs = max_interval_secs;
+
+        emit!(ScheduleUpdated {
+            mint: mint_manager.mint,
+            min_interval_secs,
+            max_interval_secs,
+            late_window_secs: mint_manager.late_window_secs,
+        });
+        Ok(())
+    }
+
+    pub fn set_late_window(ctx: Context<UpdateMintManager>, late_window_secs: i64) -> Result<()> {
+        require!(late_window_secs >= 0, RandomnessRequestError::InvalidLateWindow);
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        mint_manager.late_window_secs = late_window_secs;
 
-        global.hour_to_next_update = hour_to_next_update;
-        global.next_update_slot = new_next_update;
-        global.mint = ctx.accounts.mint.key();
+        emit!(ScheduleUpdated {
+            mint: mint_manager.mint,
+            min_interval_secs: mint_manager.min_interval_secs,
+            max_interval_secs: mint_manager.max_interval_secs,
+            late_window_secs,
+        });
+        Ok(())
+    }
+
+    // TWO STEP TRANSFER, PROPOSING AGAIN OVERWRITES THE PENDING AUTHORITY
+    pub fn propose_authority(ctx: Context<UpdateAuthority>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.mint_manager.pending_authority = Some(new_authority);
+
+        emit!(AuthorityProposed {
+            mint: ctx.accounts.mint_manager.mint,
+            authority: ctx.accounts.authority.key(),
+            pending_authority: new_authority,
+        });
+        Ok(())
+    }
 
-        global.switchboard_function = ctx.accounts.switchboard_function.key();
-        global.attestation_program_state = ctx.accounts.switchboard_state.key();
-        global.attestation_queue = ctx.accounts.switchboard_attestation_queue.key();
-        global.switchboard_request = None;
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        mint_manager.authority = mint_manager.pending_authority.take();
 
-        global.current_transfer_fee_bp = 100_00; // 100%
+        emit!(AuthorityAccepted {
+            mint: mint_manager.mint,
+            authority: ctx.accounts.pending_authority.key(),
+        });
+        Ok(())
+    }
+
+    // NO WAY BACK, SETTINGS ARE FROZEN FOR GOOD
+    pub fn renounce_authority(ctx: Context<RemoveAuthority>) -> Result<()> {
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        mint_manager.authority = None;
+        mint_manager.pending_authority = None;
 
+        emit!(AuthorityRenounced {
+            mint: mint_manager.mint,
+        });
         Ok(())
     }
 
-    // TODO: WITHDRAW WITHHELD TOKENS => Anyone can trigger
-    // MINT GOVERNANCE => Send withdraw_withheld_tokens to incinerator
-    pub fn collect_and_burn_from_account(ctx: Context<CollectAndBurn>) -> Result<()> {
+    pub fn collect_and_burn_from_account<'info>(
+        ctx: Context<'_, '_, '_, 'info, CollectAndBurn<'info>>,
+    ) -> Result<()> {
         let accounts: Vec<&Pubkey> = ctx.remaining_accounts.iter().map(|acc| acc.key).collect();
 
-        let seeds = &[GLOBAL_SEED, &[ctx.bumps.global]];
+        let mint_key = ctx.accounts.mint.key();
+        let seeds = &[MINT_MANAGER_SEED, mint_key.as_ref(), &[ctx.bumps.mint_manager]];
+        let destination = withdraw_destination(ctx.accounts)?;
         let withdraw_ix = withdraw_withheld_tokens_from_accounts(
             &ctx.accounts.token_program.key(),
             &ctx.accounts.mint.key(),
-            &ctx.accounts.global_ata.key(),
-            &ctx.accounts.global.key(),
-            &[&ctx.accounts.global.key()],
+            destination.key,
+            &ctx.accounts.mint_manager.key(),
+            &[&ctx.accounts.mint_manager.key()],
             &accounts,
         )?;
-        invoke_signed(
-            &withdraw_ix,
-            &[
-                ctx.accounts.mint.to_account_info(),
-                ctx.accounts.global.to_account_info(),
-                ctx.accounts.global_ata.to_account_info(),
-            ],
-            &[seeds],
-        )?;
+        let mut account_infos = vec![
+            ctx.accounts.mint.to_account_info(),
+            destination,
+            ctx.accounts.mint_manager.to_account_info(),
+        ];
+        account_infos.extend_from_slice(ctx.remaining_accounts);
+        invoke_signed(&withdraw_ix, &account_infos, &[seeds])?;
 
-        // BURN
-        let cpi_program = ctx.accounts.token_program.to_account_info(); // The program that we are calling
-        let cpi_accounts = Burn {
-            // The instruction followed by the parameters required
-            mint: ctx.accounts.mint.to_account_info(),
-            from: ctx.accounts.global_ata.to_account_info(),
-            authority: ctx.accounts.global.to_account_info(),
-        };
-        // ::new since the signer has already sign the transaction
-        burn(
-            CpiContext::new_with_signer(cpi_program, cpi_accounts, &[seeds]),
-            ctx.accounts.global_ata.amount,
+        // BURN / TREASURY / SPLIT
+        let (withdrawn, burned, to_treasury, to_rewards) =
+            distribute_collected(ctx.accounts, &[seeds])?;
+        ctx.accounts.mint_manager.record_collection(
+            withdrawn,
+            burned,
+            to_treasury,
+            to_rewards,
+            Clock::get()?.slot,
+        );
+
+        emit!(FeesCollected {
+            mint: mint_key,
+            amount: withdrawn,
+            to_treasury,
+            to_rewards,
+        });
+        if burned > 0 {
+            emit!(TokensBurned {
+                mint: mint_key,
+                source: ctx.accounts.mint_manager_ata.key(),
+                amount: burned,
+            });
+        }
+
+        Ok(())
+    }
+
+    // SWEEP WITHHELD FEES INTO THE MINT, NO AUTHORITY NEEDED
+    // collect_and_burn_from_mint then withdraws and burns in one step
+    pub fn harvest_withheld_tokens_to_mint<'info>(
+        ctx: Context<'_, '_, '_, 'info, HarvestToMint<'info>>,
+    ) -> Result<()> {
+        let sources: Vec<&Pubkey> = ctx.remaining_accounts.iter().map(|acc| acc.key).collect();
+        let harvest_ix = transfer_fee_ix::harvest_withheld_tokens_to_mint(
+            &ctx.accounts.token_program.key(),
+            &ctx.accounts.mint.key(),
+            &sources,
         )?;
+        let mut account_infos = vec![ctx.accounts.mint.to_account_info()];
+        account_infos.extend_from_slice(ctx.remaining_accounts);
+        invoke(&harvest_ix, &account_infos)?;
+
+        emit!(WithheldHarvested {
+            mint: ctx.accounts.mint.key(),
+            source_count: sources.len() as u32,
+        });
 
         Ok(())
     }
+
     pub fn collect_and_burn_from_mint(ctx: Context<CollectAndBurn>) -> Result<()> {
-        let seeds = &[GLOBAL_SEED, &[ctx.bumps.global]];
+        let mint_key = ctx.accounts.mint.key();
+        let seeds = &[MINT_MANAGER_SEED, mint_key.as_ref(), &[ctx.bumps.mint_manager]];
+        let destination = withdraw_destination(ctx.accounts)?;
         let withdraw_ix = withdraw_withheld_tokens_from_mint(
             &ctx.accounts.token_program.key(),
             &ctx.accounts.mint.key(),
-            &ctx.accounts.global_ata.key(),
-            &ctx.accounts.global.key(),
-            &[&ctx.accounts.global.key()],
+            destination.key,
+            &ctx.accounts.mint_manager.key(),
+            &[&ctx.accounts.mint_manager.key()],
         )?;
         invoke_signed(
             &withdraw_ix,
             &[
                 ctx.accounts.mint.to_account_info(),
-                ctx.accounts.global.to_account_info(),
-                ctx.accounts.global_ata.to_account_info(),
+                destination,
+                ctx.accounts.mint_manager.to_account_info(),
             ],
             &[seeds],
         )?;
 
-        // BURN
-        let cpi_program = ctx.accounts.token_program.to_account_info(); // The program that we are calling
-        let cpi_accounts = Burn {
-            // The instruction followed by the parameters required
-            mint: ctx.accounts.mint.to_account_info(),
-            from: ctx.accounts.global_ata.to_account_info(),
-            authority: ctx.accounts.global.to_account_info(),
-        };
-        // ::new since the signer has already sign the transaction
-        burn(
-            CpiContext::new_with_signer(cpi_program, cpi_accounts, &[seeds]),
-            ctx.accounts.global_ata.amount,
-        )?;
+        // BURN / TREASURY / SPLIT
+        let (withdrawn, burned, to_treasury, to_rewards) =
+            distribute_collected(ctx.accounts, &[seeds])?;
+        ctx.accounts.mint_manager.record_collection(
+            withdrawn,
+            burned,
+            to_treasury,
+            to_rewards,
+            Clock::get()?.slot,
+        );
+
+        emit!(FeesCollected {
+            mint: mint_key,
+            amount: withdrawn,
+            to_treasury,
+            to_rewards,
+        });
+        if burned > 0 {
+            emit!(TokensBurned {
+                mint: mint_key,
+                source: ctx.accounts.mint_manager_ata.key(),
+                amount: burned,
+            });
+        }
 
         Ok(())
     }
 
-    pub fn trigger_update(ctx: Context<TriggerUpdate>, result: u64) -> anchor_lang::Result<()> {
-        let global = &mut ctx.accounts.global;
-        let result8 = result as u8;
+    pub fn trigger_update(
+        ctx: Context<TriggerUpdate>,
+        params: TriggerUpdateParams,
+    ) -> anchor_lang::Result<()> {
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        params.validate()?;
 
-        msg!("CRANK TRIGGER");
-        if !(0..MAX_RAND_VALUE).contains(&result8) {
-            return Err(error!(RandomnessRequestError::RandomResultOutOfBounds));
-        }
+        // The trigger below schedules the next run, the escrow has to pay for it
+        let cost = cost_per_run(
+            &*ctx.accounts.switchboard_attestation_queue.load()?,
+            &*ctx.accounts.switchboard_function.load()?,
+            mint_manager.bounty,
+        );
+        require!(
+            ctx.accounts.switchboard_request_escrow.amount >= cost,
+            RandomnessRequestError::InsufficientEscrow
+        );
 
-        let current_slot = Clock::get()?.slot as u64;
+        let clock = Clock::get()?;
+        let current_slot = clock.slot;
 
-        // Update next_update_slot & update hour_to_next_update
-        let hour_to_next_update = (((current_slot + result8 as u64) % 24) + 1) as u8;
-        let new_next_update = current_slot + (HOURLY_SLOTS * hour_to_next_update as u16) as u64;
-        global.hour_to_next_update = hour_to_next_update;
-        global.next_update_slot = new_next_update;
+        // Update next_update_ts & update update_interval_secs
+        let delay = mint_manager.pick_interval(params.interval_random());
+        let new_next_update = clock
+            .unix_timestamp
+            .checked_add(delay)
+            .ok_or_else(|| error!(RandomnessRequestError::NextUpdateOverflow))?;
+        mint_manager.update_interval_secs = delay;
+        mint_manager.next_update_ts = new_next_update;
 
-        // Update mint with new current_transfer_fee_bp
-        let new_transfer_fee = result as u16 % 7 * 10 * 100;
-        global.current_transfer_fee_bp = new_transfer_fee;
-        // UPDATE MINT TRANSFER FEE
-        let seeds = &[GLOBAL_SEED, &[ctx.bumps.global]];
-        let update_transfer_fee_ix = set_transfer_fee(
-            &ctx.accounts.token_program.key(),
-            &ctx.accounts.mint.key(),
-            &global.key(),
-            &[&global.key()],
-            new_transfer_fee,
-            0,
-        )?;
-        invoke_signed(
-            &update_transfer_fee_ix,
-            &[
-                ctx.accounts.mint.to_account_info(),
-                global.to_account_info(),
-            ],
-            &[seeds],
-        )?;
+        let mint_key = ctx.accounts.mint.key();
+        let seeds = &[MINT_MANAGER_SEED, mint_key.as_ref(), &[ctx.bumps.mint_manager]];
+        // Paused or pinned by an override, only the schedule moves on
+        let fee_update = if mint_manager.fee_updates_suspended(current_slot) {
+            None
+        } else {
+            // Update mint with new current_transfer_fee_bp
+            let old_transfer_fee = mint_manager.current_transfer_fee_bp;
+            let new_transfer_fee =
+                mint_manager.bound_fee(mint_manager.pick_fee_tier(params.fee_random())?);
+            // UPDATE MINT TRANSFER FEE
+            set_mint_transfer_fee(
+                &ctx.accounts.token_program_22.to_account_info(),
+                &ctx.accounts.mint.to_account_info(),
+                &mint_manager.to_account_info(),
+                &[seeds],
+                new_transfer_fee,
+                mint_manager.max_fee,
+            )?;
+            mint_manager.record_fee_set(new_transfer_fee, clock.epoch);
+            Some((old_transfer_fee, new_transfer_fee))
+        };
 
         // Trigger the Switchboard request
         // This will instruct the off-chain oracles to execute your docker container and relay
         // the result back to our program via the 'settle' instruction.
         let trigger_ctx = FunctionRequestTrigger {
             request: ctx.accounts.switchboard_request.to_account_info(),
-            authority: global.to_account_info(),
+            authority: mint_manager.to_account_info(),
+            escrow: ctx.accounts.switchboard_request_escrow.to_account_info(),
+            function: ctx.accounts.switchboard_function.to_account_info(),
+            state: ctx.accounts.switchboard_state.to_account_info(),
+            attestation_queue: ctx.accounts.switchboard_attestation_queue.to_account_info(),
+            payer: mint_manager.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        mint_manager.valid_after_slot = valid_after_slot(current_slot, delay);
+        trigger_ctx.invoke_signed(
+            ctx.accounts.switchboard.clone(),
+            // bounty - the amount of SOL to pay the Switchboard Function for executing the request
+            Some(mint_manager.bounty),
+            // slots_until_expiration - the number of slots until the request expires
+            None,
+            // valid_after_slot - the slot when the request can be executed
+            Some(mint_manager.valid_after_slot),
+            // MintManager PDA seeds
+            &[seeds],
+        )?;
+
+        match fee_update {
+            Some((old_bp, new_bp)) => emit!(FeeUpdated {
+                mint: mint_key,
+                old_bp,
+                new_bp,
+                max_fee: mint_manager.max_fee,
+                randomness: params.randomness,
+                next_update_ts: new_next_update,
+            }),
+            None => emit!(FeeUpdateSkipped {
+                mint: mint_key,
+                paused: mint_manager.paused,
+                fee_override_until_slot: mint_manager.fee_override_until_slot,
+                next_update_ts: new_next_update,
+            }),
+        }
+
+        Ok(())
+    }
+
+    // ORACLE MISSED ITS RUN, ANYONE CAN KICK THE REQUEST AGAIN
+    pub fn retrigger_update(ctx: Context<RetriggerUpdate>) -> Result<()> {
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+
+        let cost = cost_per_run(
+            &*ctx.accounts.switchboard_attestation_queue.load()?,
+            &*ctx.accounts.switchboard_function.load()?,
+            mint_manager.bounty,
+        );
+        require!(
+            ctx.accounts.switchboard_request_escrow.amount >= cost,
+            RandomnessRequestError::InsufficientEscrow
+        );
+
+        let seeds = &[MINT_MANAGER_SEED, mint_manager.mint.as_ref(), &[ctx.bumps.mint_manager]];
+        let trigger_ctx = FunctionRequestTrigger {
+            request: ctx.accounts.switchboard_request.to_account_info(),
+            authority: mint_manager.to_account_info(),
             escrow: ctx.accounts.switchboard_request_escrow.to_account_info(),
             function: ctx.accounts.switchboard_function.to_account_info(),
             state: ctx.accounts.switchboard_state.to_account_info(),
             attestation_queue: ctx.accounts.switchboard_attestation_queue.to_account_info(),
-            payer: global.to_account_info(),
+            payer: ctx.accounts.payer.to_account_info(),
             token_program: ctx.accounts.token_program.to_account_info(),
             system_program: ctx.accounts.system_program.to_account_info(),
         };
         trigger_ctx.invoke_signed(
             ctx.accounts.switchboard.clone(),
             // bounty - the amount of SOL to pay the Switchboard Function for executing the request
+            Some(mint_manager.bounty),
+            // slots_until_expiration - the number of slots until the request expires
+            None,
+            // valid_after_slot - executable right away
+            None,
+            // MintManager PDA seeds
+            &[seeds],
+        )?;
+
+        // Restart the late window so the request is not re-triggered every slot
+        mint_manager.next_update_ts = now;
+        mint_manager.valid_after_slot = clock.slot;
+
+        emit!(RequestRetriggered {
+            mint: mint_manager.mint,
+            payer: ctx.accounts.payer.key(),
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    // CLOSE THE OLD REQUEST, RECREATE IT AGAINST THE NEW FUNCTION AND KEEP THE SCHEDULE
+    // e.g. after rebuilding the container changed the MRENCLAVE
+    pub fn set_switchboard_function(ctx: Context<SetSwitchboardFunction>) -> Result<()> {
+        let mint_manager = &ctx.accounts.mint_manager;
+        let mint_key = mint_manager.mint;
+        let mint_manager_key = mint_manager.key();
+        let seeds = &[MINT_MANAGER_SEED, mint_key.as_ref(), &[ctx.bumps.mint_manager]];
+
+        // Close the old request, the escrow balance goes to the mint manager wSOL account
+        let request_close_ctx = FunctionRequestClose {
+            request: ctx.accounts.old_switchboard_request.clone(),
+            authority: mint_manager.to_account_info(),
+            escrow: ctx.accounts.old_switchboard_request_escrow.clone(),
+            function: ctx.accounts.old_switchboard_function.clone(),
+            sol_dest: ctx.accounts.authority.to_account_info(),
+            escrow_dest: ctx.accounts.mint_manager_wsol.to_account_info(),
+            state: ctx.accounts.switchboard_state.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        request_close_ctx.invoke_signed(ctx.accounts.switchboard.clone(), Some(true), &[seeds])?;
+
+        let container_params = ContainerParams {
+            program_id: crate::id(),
+            mint_manager: mint_manager_key,
+            mint: mint_key,
+            escrow: ctx.accounts.switchboard_request_escrow.key(),
+        }
+        .encode();
+
+        // Create the new Switchboard request account.
+        let request_init_ctx = FunctionRequestInit {
+            request: ctx.accounts.switchboard_request.clone(),
+            authority: mint_manager.to_account_info(), // AUTHORITY OVER REQUEST
+            function: ctx.accounts.switchboard_function.to_account_info(),
+            function_authority: None,
+            escrow: ctx.accounts.switchboard_request_escrow.clone(), // ESCROW ACCOUNT THAT WILL BE PAYING FOR FEES
+            mint: ctx.accounts.switchboard_mint.to_account_info(),   // NATIVE SOL MINT
+            state: ctx.accounts.switchboard_state.to_account_info(),
+            attestation_queue: ctx.accounts.switchboard_attestation_queue.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        };
+        request_init_ctx.invoke(
+            ctx.accounts.switchboard.clone(),
+            Some(container_params.len() as u32),
+            Some(container_params),
             None,
+        )?;
+
+        // Carry the old escrow balance over, then give the wSOL account rent back
+        ctx.accounts.mint_manager_wsol.reload()?;
+        let escrow_amount = ctx.accounts.mint_manager_wsol.amount;
+        if escrow_amount > 0 {
+            anchor_spl::token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token::Transfer {
+                        from: ctx.accounts.mint_manager_wsol.to_account_info(),
+                        to: ctx.accounts.switchboard_request_escrow.clone(),
+                        authority: mint_manager.to_account_info(),
+                    },
+                    &[seeds],
+                ),
+                escrow_amount,
+            )?;
+        }
+        anchor_spl::token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::CloseAccount {
+                account: ctx.accounts.mint_manager_wsol.to_account_info(),
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: mint_manager.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+
+        // Trigger it for the update already scheduled
+        let clock = Clock::get()?;
+        let valid_after =
+            valid_after_slot(clock.slot, mint_manager.next_update_ts - clock.unix_timestamp);
+        let trigger_ctx = FunctionRequestTrigger {
+            request: ctx.accounts.switchboard_request.to_account_info(),
+            authority: mint_manager.to_account_info(),
+            escrow: ctx.accounts.switchboard_request_escrow.to_account_info(),
+            function: ctx.accounts.switchboard_function.to_account_info(),
+            state: ctx.accounts.switchboard_state.to_account_info(),
+            attestation_queue: ctx.accounts.switchboard_attestation_queue.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        trigger_ctx.invoke_signed(
+            ctx.accounts.switchboard.clone(),
+            // bounty - the amount of SOL to pay the Switchboard Function for executing the request
+            Some(mint_manager.bounty),
             // slots_until_expiration - the number of slots until the request expires
             None,
             // valid_after_slot - the slot when the request can be executed
-            Some(new_next_update),
-            // Lottery PDA seeds
-            &[&[GLOBAL_SEED, &[ctx.bumps.global]]],
+            Some(valid_after),
+            // MintManager PDA seeds
+            &[seeds],
+        )?;
+
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        let old_function = mint_manager.switchboard_function;
+        mint_manager.switchboard_function = ctx.accounts.switchboard_function.key();
+        mint_manager.attestation_queue = ctx.accounts.switchboard_attestation_queue.key();
+        mint_manager.switchboard_request = Some(ctx.accounts.switchboard_request.key());
+        mint_manager.switchboard_request_escrow = ctx.accounts.switchboard_request_escrow.key();
+        mint_manager.valid_after_slot = valid_after;
+
+        emit!(SwitchboardFunctionUpdated {
+            mint: mint_key,
+            old_function,
+            function: mint_manager.switchboard_function,
+            request: ctx.accounts.switchboard_request.key(),
+            escrow: mint_manager.switchboard_request_escrow,
+            escrow_amount,
+        });
+        Ok(())
+    }
+
+    // PERMISSIONLESS, ANYONE CAN KEEP THE UPDATES PAID FOR
+    pub fn deposit_escrow(ctx: Context<DepositEscrow>, amount: u64) -> Result<()> {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.depositor.to_account_info(),
+                    to: ctx.accounts.switchboard_request_escrow.to_account_info(),
+                },
+            ),
+            amount,
         )?;
+        anchor_spl::token::sync_native(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::SyncNative {
+                account: ctx.accounts.switchboard_request_escrow.to_account_info(),
+            },
+        ))?;
+        ctx.accounts.switchboard_request_escrow.reload()?;
+
+        emit!(EscrowDeposited {
+            mint: ctx.accounts.mint_manager.mint,
+            depositor: ctx.accounts.depositor.key(),
+            amount,
+            balance: ctx.accounts.switchboard_request_escrow.amount,
+        });
+        Ok(())
+    }
+
+    // PAID TO THE ORACLE ON TOP OF THE QUEUE REWARD, APPLIED FROM THE NEXT TRIGGER
+    pub fn set_bounty(ctx: Context<UpdateMintManager>, bounty: u64) -> Result<()> {
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        mint_manager.bounty = bounty;
+
+        emit!(BountyUpdated {
+            mint: mint_manager.mint,
+            bounty,
+        });
+        Ok(())
+    }
+
+    // HOW MANY MORE RUNS THE ESCROW PAYS FOR, MEANT TO BE SIMULATED
+    pub fn escrow_runway(ctx: Context<ViewEscrowRunway>) -> Result<EscrowRunway> {
+        let cost = cost_per_run(
+            &*ctx.accounts.switchboard_attestation_queue.load()?,
+            &*ctx.accounts.switchboard_function.load()?,
+            ctx.accounts.mint_manager.bounty,
+        );
+        Ok(EscrowRunway::new(
+            ctx.accounts.switchboard_request_escrow.amount,
+            cost,
+        ))
+    }
+
+    // PINS THE FEE ON THE MINT, TRIGGER_UPDATE KEEPS IT UNTIL THE OVERRIDE RUNS OUT
+    // TOKEN22 STILL CHARGES THE OLD FEE UNTIL TWO EPOCHS FROM NOW, SEE FeeOverrideSet
+    pub fn set_fee_override(
+        ctx: Context<SetFeeOverride>,
+        transfer_fee_bp: u16,
+        duration_slots: u64,
+    ) -> Result<()> {
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        mint_manager.validate_fee_override(transfer_fee_bp)?;
+        let mint_key = ctx.accounts.mint.key();
+        let seeds = &[MINT_MANAGER_SEED, mint_key.as_ref(), &[ctx.bumps.mint_manager]];
+        set_mint_transfer_fee(
+            &ctx.accounts.token_program_22.to_account_info(),
+            &ctx.accounts.mint.to_account_info(),
+            &mint_manager.to_account_info(),
+            &[seeds],
+            transfer_fee_bp,
+            mint_manager.max_fee,
+        )?;
+
+        let old_bp = mint_manager.current_transfer_fee_bp;
+        let clock = Clock::get()?;
+        mint_manager.record_fee_set(transfer_fee_bp, clock.epoch);
+        mint_manager.fee_override_until_slot = clock.slot.saturating_add(duration_slots);
+
+        emit!(FeeOverrideSet {
+            mint: mint_key,
+            old_bp,
+            new_bp: transfer_fee_bp,
+            effective_epoch: mint_manager.newer_transfer_fee.epoch,
+            until_slot: mint_manager.fee_override_until_slot,
+        });
+        Ok(())
+    }
+
+    // EMERGENCY SWITCH, TRIGGER_UPDATE ONLY RESCHEDULES WHILE PAUSED
+    pub fn set_paused(ctx: Context<UpdateMintManager>, paused: bool) -> Result<()> {
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        mint_manager.paused = paused;
+
+        emit!(PauseUpdated {
+            mint: mint_manager.mint,
+            paused,
+        });
+        Ok(())
+    }
+
+    // EACH BOUND CAN ONLY BE SET ONCE, NONE LEAVES IT AS IT IS
+    pub fn set_fee_guardrails(
+        ctx: Context<UpdateMintManager>,
+        fee_ceiling_bp: Option<u16>,
+        max_fee_delta_bp: Option<u16>,
+    ) -> Result<()> {
+        let mint_manager = &mut ctx.accounts.mint_manager;
+        if let Some(fee_ceiling_bp) = fee_ceiling_bp {
+            require!(
+                mint_manager.fee_ceiling_bp.is_none(),
+                RandomnessRequestError::FeeGuardrailAlreadySet
+            );
+            require!(
+                fee_ceiling_bp <= MAX_FEE_BP,
+                RandomnessRequestError::InvalidFeeGuardrails
+            );
+            mint_manager.fee_ceiling_bp = Some(fee_ceiling_bp);
+        }
+        if let Some(max_fee_delta_bp) = max_fee_delta_bp {
+            require!(
+                mint_manager.max_fee_delta_bp.is_none(),
+                RandomnessRequestError::FeeGuardrailAlreadySet
+            );
+            require!(
+                max_fee_delta_bp > 0,
+                RandomnessRequestError::InvalidFeeGuardrails
+            );
+            mint_manager.max_fee_delta_bp = Some(max_fee_delta_bp);
+        }
 
+        emit!(FeeGuardrailsSet {
+            mint: mint_manager.mint,
+            fee_ceiling_bp: mint_manager.fee_ceiling_bp,
+            max_fee_delta_bp: mint_manager.max_fee_delta_bp,
+        });
         Ok(())
     }
 }
@@ -1,29 +1,415 @@
 use crate::*;
+use spl_token_2022::extension::transfer_fee::TransferFee;
 
-pub const GLOBAL_SEED: &[u8] = b"global";
-pub const HOURLY_SLOTS: u16 = 9000; // ~1 hour at 400 ms/slot
-pub const MAX_RAND_VALUE: u8 = 254;
+pub const MINT_MANAGER_SEED: &[u8] = b"mint_manager";
+pub const SECONDS_PER_HOUR: i64 = 3600;
+pub const MAX_INTERVAL_SECS: i64 = 85 * SECONDS_PER_HOUR;
+// Only used to hint Switchboard's valid_after_slot, below the 400ms target so that slots
+// running fast do not let the oracle in before next_update_ts
+pub const MIN_SLOT_DURATION_MS: u64 = 350;
+pub const MAX_FEE_TIERS: usize = 16;
+pub const MAX_FEE_BP: u16 = 10_000;
+pub const TRIGGER_UPDATE_VERSION: u8 = 1;
+
+// TRIGGER_UPDATE IXN DATA, BUMP THE VERSION WHENEVER THE LAYOUT CHANGES
+// v1: [0]: version | [1-32]: randomness
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct TriggerUpdateParams {
+    pub version: u8,
+    /// Random buffer from the Switchboard function.
+    pub randomness: [u8; 32],
+}
+
+impl TriggerUpdateParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.version == TRIGGER_UPDATE_VERSION,
+            RandomnessRequestError::UnsupportedParamsVersion
+        );
+        Ok(())
+    }
+
+    /// Bytes [0-8], only drives the fee tier.
+    pub fn fee_random(&self) -> u64 {
+        u64::from_le_bytes(self.randomness[0..8].try_into().unwrap())
+    }
+
+    /// Bytes [8-16], only drives the update interval.
+    pub fn interval_random(&self) -> u64 {
+        u64::from_le_bytes(self.randomness[8..16].try_into().unwrap())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct FeeTier {
+    /// Transfer fee in basis point.
+    pub bp: u16,
+    /// Relative chance of the tier being picked.
+    pub weight: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace, PartialEq, Eq)]
+pub enum FeeDestination {
+    /// Burn everything collected.
+    #[default]
+    Burn,
+    /// Send everything collected to the treasury.
+    Treasury,
+    /// Split in basis point, has to sum up to 10000.
+    Split {
+        burn_bp: u16,
+        treasury_bp: u16,
+        rewards_bp: u16,
+    },
+}
+
+impl FeeDestination {
+    pub fn validate(&self) -> Result<()> {
+        if let FeeDestination::Split {
+            burn_bp,
+            treasury_bp,
+            rewards_bp,
+        } = *self
+        {
+            require!(
+                burn_bp as u32 + treasury_bp as u32 + rewards_bp as u32 == MAX_FEE_BP as u32,
+                RandomnessRequestError::InvalidFeeSplit
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns (burn, treasury, rewards), rounding dust goes to burn.
+    pub fn split(&self, amount: u64) -> (u64, u64, u64) {
+        match *self {
+            FeeDestination::Burn => (amount, 0, 0),
+            FeeDestination::Treasury => (0, amount, 0),
+            FeeDestination::Split {
+                treasury_bp,
+                rewards_bp,
+                ..
+            } => {
+                let treasury = (amount as u128 * treasury_bp as u128 / MAX_FEE_BP as u128) as u64;
+                let rewards = (amount as u128 * rewards_bp as u128 / MAX_FEE_BP as u128) as u64;
+                (amount - treasury - rewards, treasury, rewards)
+            }
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum MaxFee {
+    /// Raw token amount, decimals included.
+    Amount(u64),
+    /// Whole tokens, scaled by the mint decimals.
+    WholeTokens(u64),
+}
+
+impl MaxFee {
+    pub fn to_amount(self, decimals: u8) -> Result<u64> {
+        match self {
+            MaxFee::Amount(amount) => Ok(amount),
+            MaxFee::WholeTokens(tokens) => 10u64
+                .checked_pow(decimals as u32)
+                .and_then(|unit| tokens.checked_mul(unit))
+                .ok_or_else(|| error!(RandomnessRequestError::MaxFeeOverflow)),
+        }
+    }
+}
+
+/// One side of the Token-2022 fee schedule, charged from `epoch` on.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct EpochFee {
+    pub epoch: u64,
+    pub transfer_fee_bp: u16,
+    /// Fee cap per transfer in raw token amount.
+    pub max_fee: u64,
+}
+
+impl From<&TransferFee> for EpochFee {
+    fn from(fee: &TransferFee) -> Self {
+        Self {
+            epoch: fee.epoch.into(),
+            transfer_fee_bp: fee.transfer_fee_basis_points.into(),
+            max_fee: fee.maximum_fee.into(),
+        }
+    }
+}
+
+impl EpochFee {
+    /// Fee withheld on a transfer of `amount`, mirrors Token-2022's rounding.
+    pub fn calculate_fee(&self, amount: u64) -> Option<u64> {
+        let fee = (amount as u128)
+            .checked_mul(self.transfer_fee_bp as u128)?
+            .checked_add(MAX_FEE_BP as u128 - 1)?
+            / MAX_FEE_BP as u128;
+        Some(u64::try_from(fee).ok()?.min(self.max_fee))
+    }
+}
+
+/// Returned by escrow_runway.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EscrowRunway {
+    /// wSOL held by the request escrow.
+    pub balance: u64,
+    /// Queue reward + function requests fee + bounty.
+    pub cost_per_run: u64,
+    /// Runs the balance covers, u64::MAX when runs are free.
+    pub runs: u64,
+}
+
+impl EscrowRunway {
+    pub fn new(balance: u64, cost_per_run: u64) -> Self {
+        Self {
+            balance,
+            cost_per_run,
+            runs: balance.checked_div(cost_per_run).unwrap_or(u64::MAX),
+        }
+    }
+}
 
 #[account]
-pub struct Global {
-    // GLOBAL SETTINGS
-    pub hour_to_next_update: u8, // ===============> MIN 1HR - MAX 85 HRS | Result / 3
-    /// The slot when the current guess was placed.
-    pub next_update_slot: u64,
-    /// Token Mint.
+#[derive(Default, InitSpace)]
+pub struct MintManager {
+    // MANAGER SETTINGS
+    /// Seconds between the last two updates, drawn within the interval bounds.
+    pub update_interval_secs: i64,
+    /// Shortest randomized update interval in seconds.
+    pub min_interval_secs: i64,
+    /// Longest randomized update interval in seconds, inclusive, at most MAX_INTERVAL_SECS.
+    pub max_interval_secs: i64,
+    /// Unix timestamp from which trigger_update is accepted.
+    pub next_update_ts: i64,
+    /// Slot the pending request was scheduled to run from, next_update_ts at the fastest slot time.
+    pub valid_after_slot: u64,
+    /// Seconds past next_update_ts before a missed request can be re-triggered.
+    pub late_window_secs: i64,
+    /// Token Mint, also the seed of this manager.
     pub mint: Pubkey,
+    /// Admin over the manager settings. None once renounced.
+    pub authority: Option<Pubkey>,
+    /// Proposed authority, must accept before taking over.
+    pub pending_authority: Option<Pubkey>,
 
     // SWITCHBOARD SETTINGS
-    // Switchboard Function Request pubkey.
+    // Switchboard Function pubkey.
     pub switchboard_function: Pubkey,
-    // Switchboard Function Request pubkey.
+    // Switchboard Attestation Program State pubkey.
     pub attestation_program_state: Pubkey,
-    // Switchboard Function Request pubkey.
+    // Switchboard Attestation Queue pubkey.
     pub attestation_queue: Pubkey,
-    // Switchboard Function Request pubkey.
+    // Switchboard Function Request pubkey, created by init_global.
     pub switchboard_request: Option<Pubkey>,
+    // Wrapped SOL escrow paying for the request.
+    pub switchboard_request_escrow: Pubkey,
+    // Lamports paid from the escrow to the oracle on top of the queue reward, per run.
+    pub bounty: u64,
 
     // TRANSFER FEE SETTINGS
-    /// The max transfer fee in basis point.
-    pub current_transfer_fee_bp: u16, // ===============> 0 - 60%, 10% Interval
+    /// Fee last set on the mint in basis point. Scheduled, Token-2022 only charges it
+    /// from newer_transfer_fee.epoch, see effective_fee.
+    pub current_transfer_fee_bp: u16, // ===============> Picked from fee_tiers
+    /// Fee cap per transfer in raw token amount, set on the mint with every update.
+    pub max_fee: u64,
+    /// Mirror of the mint's fee schedule: charged before newer_transfer_fee.epoch.
+    pub older_transfer_fee: EpochFee,
+    /// Mirror of the mint's fee schedule: the last fee set, charged from its epoch.
+    pub newer_transfer_fee: EpochFee,
+    /// Weighted fee table trigger_update picks from.
+    #[max_len(MAX_FEE_TIERS)]
+    pub fee_tiers: Vec<FeeTier>,
+    /// trigger_update keeps the manually set fee before this slot.
+    pub fee_override_until_slot: u64,
+    /// Emergency switch, trigger_update only reschedules while set.
+    pub paused: bool,
+    /// Hard cap on the fee in bp, immutable once set.
+    pub fee_ceiling_bp: Option<u16>,
+    /// Largest bp move of a single update, immutable once set.
+    pub max_fee_delta_bp: Option<u16>,
+
+    // COLLECTION SETTINGS
+    /// What happens to the collected withheld tokens.
+    pub fee_destination: FeeDestination,
+    /// Treasury token account, Pubkey::default() when unset.
+    pub treasury: Pubkey,
+    /// Rewards vault token account, Pubkey::default() when unset.
+    pub rewards_vault: Pubkey,
+
+    // COLLECTION STATS, LIFETIME TOTALS IN RAW TOKEN AMOUNT
+    /// Withheld tokens withdrawn by the collect instructions.
+    pub total_collected: u64,
+    /// Collected tokens burned.
+    pub total_burned: u64,
+    /// Collected tokens received by the treasury, net of the transfer fee.
+    pub total_to_treasury: u64,
+    /// Collected tokens received by the rewards vault, net of the transfer fee.
+    pub total_to_rewards: u64,
+    /// Number of collect instructions executed.
+    pub collection_count: u64,
+    /// The slot of the last collect.
+    pub last_collection_slot: u64,
+}
+
+impl MintManager {
+    /// 0 - 60%, 10% Interval, equal weights.
+    pub fn default_fee_tiers() -> Vec<FeeTier> {
+        (0..7).map(|i| FeeTier { bp: i * 10 * 100, weight: 1 }).collect()
+    }
+
+    /// One epoch at the fastest slot time. Token-2022 charges a new fee from the second epoch
+    /// boundary after it is set, any update sooner than this replaces the fee before that.
+    pub fn min_effective_interval_secs(epoch_schedule: &EpochSchedule) -> i64 {
+        (epoch_schedule.slots_per_epoch.saturating_mul(MIN_SLOT_DURATION_MS) / 1000) as i64
+    }
+
+    pub fn validate_interval_bounds(
+        min_interval_secs: i64,
+        max_interval_secs: i64,
+        epoch_schedule: &EpochSchedule,
+    ) -> Result<()> {
+        require!(
+            min_interval_secs > 0
+                && min_interval_secs <= max_interval_secs
+                && max_interval_secs <= MAX_INTERVAL_SECS,
+            RandomnessRequestError::InvalidIntervalBounds
+        );
+        require!(
+            max_interval_secs >= Self::min_effective_interval_secs(epoch_schedule),
+            RandomnessRequestError::IntervalBelowEpoch
+        );
+        Ok(())
+    }
+
+    /// Late by the clock and by the slot estimate. valid_after_slot lands after next_update_ts
+    /// when slots run slower than the estimate, the oracle gets the late window from there.
+    pub fn request_stale(&self, clock: &Clock) -> bool {
+        clock.unix_timestamp > self.next_update_ts.saturating_add(self.late_window_secs)
+            && clock.slot > valid_after_slot(self.valid_after_slot, self.late_window_secs)
+    }
+
+    /// Randomized fee updates are off while paused or overridden.
+    pub fn fee_updates_suspended(&self, slot: u64) -> bool {
+        self.paused || slot < self.fee_override_until_slot
+    }
+
+    /// Uniform pick in [min_interval_secs, max_interval_secs].
+    pub fn pick_interval(&self, random: u64) -> i64 {
+        let window = (self.max_interval_secs - self.min_interval_secs) as u64 + 1;
+        self.min_interval_secs + (random % window) as i64
+    }
+
+    pub fn validate_fee_tiers(fee_tiers: &[FeeTier]) -> Result<()> {
+        require!(
+            !fee_tiers.is_empty() && fee_tiers.len() <= MAX_FEE_TIERS,
+            RandomnessRequestError::InvalidFeeTiers
+        );
+        require!(
+            fee_tiers.iter().all(|tier| tier.bp <= MAX_FEE_BP),
+            RandomnessRequestError::InvalidFeeTiers
+        );
+        require!(
+            fee_tiers.iter().any(|tier| tier.weight > 0),
+            RandomnessRequestError::InvalidFeeTiers
+        );
+        Ok(())
+    }
+
+    pub fn record_collection(
+        &mut self,
+        withdrawn: u64,
+        burned: u64,
+        to_treasury: u64,
+        to_rewards: u64,
+        slot: u64,
+    ) {
+        self.total_collected = self.total_collected.saturating_add(withdrawn);
+        self.total_burned = self.total_burned.saturating_add(burned);
+        self.total_to_treasury = self.total_to_treasury.saturating_add(to_treasury);
+        self.total_to_rewards = self.total_to_rewards.saturating_add(to_rewards);
+        self.collection_count = self.collection_count.saturating_add(1);
+        self.last_collection_slot = slot;
+    }
+
+    /// Tracks a set_transfer_fee CPI the way Token-2022 rolls its schedule.
+    pub fn record_fee_set(&mut self, transfer_fee_bp: u16, epoch: u64) {
+        if self.newer_transfer_fee.epoch <= epoch {
+            self.older_transfer_fee = self.newer_transfer_fee;
+        }
+        self.newer_transfer_fee = EpochFee {
+            epoch: epoch.saturating_add(2),
+            transfer_fee_bp,
+            max_fee: self.max_fee,
+        };
+        self.current_transfer_fee_bp = transfer_fee_bp;
+    }
+
+    /// The fee Token-2022 charges in `epoch`.
+    pub fn effective_fee(&self, epoch: u64) -> EpochFee {
+        if epoch >= self.newer_transfer_fee.epoch {
+            self.newer_transfer_fee
+        } else {
+            self.older_transfer_fee
+        }
+    }
+
+    /// Fee withheld on a transfer of `amount` in `epoch`, mirrors Token-2022's rounding.
+    pub fn calculate_fee(&self, amount: u64, epoch: u64) -> Option<u64> {
+        self.effective_fee(epoch).calculate_fee(amount)
+    }
+
+    /// Current fee as the guardrails see it, anything above the ceiling counts as the ceiling.
+    fn bounded_current_fee(&self) -> u16 {
+        self.current_transfer_fee_bp
+            .min(self.fee_ceiling_bp.unwrap_or(MAX_FEE_BP))
+    }
+
+    /// Pulls a picked fee within the ceiling and the max change from the current fee.
+    pub fn bound_fee(&self, transfer_fee_bp: u16) -> u16 {
+        let ceiling = self.fee_ceiling_bp.unwrap_or(MAX_FEE_BP);
+        let transfer_fee_bp = transfer_fee_bp.min(ceiling);
+        match self.max_fee_delta_bp {
+            Some(max_delta) => {
+                let current = self.bounded_current_fee();
+                transfer_fee_bp.clamp(
+                    current.saturating_sub(max_delta),
+                    current.saturating_add(max_delta).min(ceiling),
+                )
+            }
+            None => transfer_fee_bp,
+        }
+    }
+
+    /// Manual fees are rejected instead of bounded.
+    pub fn validate_fee_override(&self, transfer_fee_bp: u16) -> Result<()> {
+        require!(
+            transfer_fee_bp <= MAX_FEE_BP,
+            RandomnessRequestError::InvalidFeeOverride
+        );
+        require!(
+            transfer_fee_bp <= self.fee_ceiling_bp.unwrap_or(MAX_FEE_BP),
+            RandomnessRequestError::FeeAboveCeiling
+        );
+        if let Some(max_delta) = self.max_fee_delta_bp {
+            require!(
+                transfer_fee_bp.abs_diff(self.bounded_current_fee()) <= max_delta,
+                RandomnessRequestError::FeeChangeTooLarge
+            );
+        }
+        Ok(())
+    }
+
+    /// Walk the cumulative weights until the random roll lands in a tier.
+    pub fn pick_fee_tier(&self, random: u64) -> Result<u16> {
+        let total_weight: u64 = self.fee_tiers.iter().map(|tier| tier.weight as u64).sum();
+        require!(total_weight > 0, RandomnessRequestError::InvalidFeeTiers);
+
+        let mut roll = random % total_weight;
+        for tier in self.fee_tiers.iter() {
+            if roll < tier.weight as u64 {
+                return Ok(tier.bp);
+            }
+            roll -= tier.weight as u64;
+        }
+        Err(error!(RandomnessRequestError::InvalidFeeTiers))
+    }
 }
@@ -1,10 +1,118 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::TokenAccount;
+use anchor_spl::token_interface::{transfer_checked, Mint, TransferChecked};
+use spl_token_2022::extension::{
+    transfer_fee::{instruction::set_transfer_fee, TransferFeeConfig},
+    BaseStateWithExtensions, StateWithExtensions,
+};
+use switchboard_solana::{invoke_signed, AttestationQueueAccountData, FunctionAccountData};
 
+use crate::{CollectAndBurn, FeeDestination, RandomnessRequestError, MIN_SLOT_DURATION_MS};
+
+// SWITCHBOARD SCHEDULES IN SLOTS, CONVERT A WALL-CLOCK DELAY TO THE EARLIEST SAFE SLOT
+pub fn valid_after_slot(current_slot: u64, seconds: i64) -> u64 {
+    let seconds = seconds.max(0) as u64;
+    current_slot.saturating_add(seconds.saturating_mul(1000) / MIN_SLOT_DURATION_MS)
+}
+
+// WHAT ONE RUN DRAWS FROM THE ESCROW: QUEUE REWARD + FUNCTION DEV FEE + OUR BOUNTY
+pub fn cost_per_run(
+    queue: &AttestationQueueAccountData,
+    function: &FunctionAccountData,
+    bounty: u64,
+) -> u64 {
+    u64::from(queue.reward)
+        .saturating_add(function.requests_fee)
+        .saturating_add(bounty)
+}
+
+// MINT HAS TO BE TOKEN22 WITH TRANSFER FEE CONFIG HANDED TO THE MINT MANAGER
+// ONLY THE MINT AUTHORITY CAN CLAIM IT, OTHERWISE ANYONE COULD FRONT-RUN THE INIT
+// Returns the fee config so the manager starts from the fee the mint actually charges
+pub fn validate_mint(
+    mint: &AccountInfo,
+    mint_manager: &Pubkey,
+    payer: &Pubkey,
+) -> Result<TransferFeeConfig> {
+    require_keys_eq!(
+        *mint.owner,
+        spl_token_2022::ID,
+        RandomnessRequestError::InvalidMintOwner
+    );
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)
+        .map_err(|_| error!(RandomnessRequestError::InvalidMint))?;
+    require!(
+        Option::<Pubkey>::from(mint_state.base.mint_authority) == Some(*payer),
+        RandomnessRequestError::InvalidMintAuthority
+    );
+    let transfer_fee_config = mint_state
+        .get_extension::<TransferFeeConfig>()
+        .map_err(|_| error!(RandomnessRequestError::MissingTransferFeeConfig))?;
+
+    require!(
+        Option::<Pubkey>::from(transfer_fee_config.transfer_fee_config_authority)
+            == Some(*mint_manager),
+        RandomnessRequestError::InvalidTransferFeeConfigAuthority
+    );
+    require!(
+        Option::<Pubkey>::from(transfer_fee_config.withdraw_withheld_authority)
+            == Some(*mint_manager),
+        RandomnessRequestError::InvalidWithdrawWithheldAuthority
+    );
+    Ok(*transfer_fee_config)
+}
+
+// TOKEN22 SCHEDULES THE NEW FEE TWO EPOCHS OUT
+pub fn set_mint_transfer_fee<'a>(
+    token_program: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    mint_manager: &AccountInfo<'a>,
+    auth_seed: &[&[&[u8]]],
+    transfer_fee_bp: u16,
+    max_fee: u64,
+) -> Result<()> {
+    let ix = set_transfer_fee(
+        token_program.key,
+        mint.key,
+        mint_manager.key,
+        &[mint_manager.key],
+        transfer_fee_bp,
+        max_fee,
+    )?;
+    invoke_signed(&ix, &[mint.clone(), mint_manager.clone()], auth_seed)?;
+    Ok(())
+}
+
+// TOKEN22 REQUIRES TRANSFER_CHECKED, THE MINT TRANSFER FEE IS WITHHELD AT DESTINATION
 pub fn transfer<'a>(
     token_program: &AccountInfo<'a>,
-    from: &Account<'a, TokenAccount>,
-    to: &Account<'a, TokenAccount>,
+    mint: &InterfaceAccount<'a, Mint>,
+    from: &AccountInfo<'a>,
+    to: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    auth_seed: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let cpi_program = token_program.clone();
+    let cpi_accounts = TransferChecked {
+        from: from.clone(),
+        mint: mint.to_account_info(),
+        to: to.clone(),
+        authority: authority.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, auth_seed);
+    transfer_checked(cpi_ctx, amount, mint.decimals)?;
+    Ok(())
+}
+
+pub fn burn<'a>(
+    token_program: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    from: &AccountInfo<'a>,
     authority: &AccountInfo<'a>,
     auth_seed: &[&[&[u8]]],
     amount: u64,
@@ -13,16 +121,108 @@ pub fn transfer<'a>(
         return Ok(());
     }
     let cpi_program = token_program.clone();
-    let cpi_accounts = anchor_spl::token::Transfer {
-        from: from.to_account_info(),
-        to: to.to_account_info(),
+    let cpi_accounts = anchor_spl::token_interface::Burn {
+        mint: mint.clone(),
+        from: from.clone(),
         authority: authority.clone(),
     };
     let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, auth_seed);
-    anchor_spl::token::transfer(cpi_ctx, amount)?;
+    anchor_spl::token_interface::burn(cpi_ctx, amount)?;
     Ok(())
 }
 
+// TREASURY MODE WITHDRAWS STRAIGHT INTO THE TREASURY, TOKEN22 CHARGES NO FEE ON WITHDRAWS
+pub fn withdraw_destination<'info>(accounts: &CollectAndBurn<'info>) -> Result<AccountInfo<'info>> {
+    match accounts.mint_manager.fee_destination {
+        FeeDestination::Treasury => Ok(accounts
+            .treasury
+            .as_ref()
+            .ok_or(RandomnessRequestError::MissingTreasury)?
+            .to_account_info()),
+        _ => Ok(accounts.mint_manager_ata.to_account_info()),
+    }
+}
+
+// SEND THE MANAGER ATA BALANCE WHERE THE FEE DESTINATION SAYS
+// Returns (withdrawn, burned, received by the treasury, received by the rewards vault),
+// forwarded amounts are net of the transfer fee Token-2022 withholds on the way
+pub fn distribute_collected(
+    accounts: &mut CollectAndBurn,
+    auth_seed: &[&[&[u8]]],
+) -> Result<(u64, u64, u64, u64)> {
+    if let FeeDestination::Treasury = accounts.mint_manager.fee_destination {
+        // ALREADY THERE, THE WITHDRAW WENT TO THE TREASURY
+        let treasury = accounts
+            .treasury
+            .as_mut()
+            .ok_or(RandomnessRequestError::MissingTreasury)?;
+        let before = treasury.amount;
+        treasury.reload()?;
+        let withdrawn = treasury.amount.saturating_sub(before);
+        return Ok((withdrawn, 0, withdrawn, 0));
+    }
+
+    // RELOAD SINCE THE WITHDRAW CHANGED THE BALANCE
+    let before = accounts.mint_manager_ata.amount;
+    accounts.mint_manager_ata.reload()?;
+    let withdrawn = accounts.mint_manager_ata.amount.saturating_sub(before);
+    let (burn_amount, treasury_amount, rewards_amount) = accounts
+        .mint_manager
+        .fee_destination
+        .split(accounts.mint_manager_ata.amount);
+
+    let token_program = accounts.token_program.to_account_info();
+    let from = accounts.mint_manager_ata.to_account_info();
+    let authority = accounts.mint_manager.to_account_info();
+    let epoch = Clock::get()?.epoch;
+    let net_of_fee = |amount: u64| {
+        amount.saturating_sub(accounts.mint_manager.calculate_fee(amount, epoch).unwrap_or(0))
+    };
+    let treasury_received = net_of_fee(treasury_amount);
+    let rewards_received = net_of_fee(rewards_amount);
+
+    if treasury_amount > 0 {
+        let treasury = accounts
+            .treasury
+            .as_ref()
+            .ok_or(RandomnessRequestError::MissingTreasury)?;
+        transfer(
+            &token_program,
+            &accounts.mint,
+            &from,
+            &treasury.to_account_info(),
+            &authority,
+            auth_seed,
+            treasury_amount,
+        )?;
+    }
+    if rewards_amount > 0 {
+        let rewards_vault = accounts
+            .rewards_vault
+            .as_ref()
+            .ok_or(RandomnessRequestError::MissingRewardsVault)?;
+        transfer(
+            &token_program,
+            &accounts.mint,
+            &from,
+            &rewards_vault.to_account_info(),
+            &authority,
+            auth_seed,
+            rewards_amount,
+        )?;
+    }
+    burn(
+        &token_program,
+        &accounts.mint.to_account_info(),
+        &from,
+        &authority,
+        auth_seed,
+        burn_amount,
+    )?;
+
+    Ok((withdrawn, burn_amount, treasury_received, rewards_received))
+}
+
 pub fn calculate_reward(annual_return_bps: u16, time_period_hours: u8, amount: u64) -> u64 {
     // Convert annual_return_bps and time_period_hours to decimal values
     let annual_return_decimal = f64::from(annual_return_bps) / 10000.0;
@@ -0,0 +1,681 @@
+//! Minimal in-process runtime for the l2 program.
+//!
+//! Programs run natively against `solana_program`'s syscall stubs, the same way
+//! solana-program-test runs native processors: l2 itself, Token, Token-2022 and the
+//! associated token program are the real processors, the system program is
+//! reimplemented and Switchboard is a stub (see `switchboard.rs`). No validator or
+//! network is needed.
+//!
+//! After every instruction, inner ones included, the runtime's account rules are checked:
+//! only writable accounts change, only the owner debits lamports, changes data or hands
+//! the account to another owner, and lamports balance across the instruction. The
+//! Switchboard stub writes the accounts it creates and closes directly instead of going
+//! through the system and token programs, so it is only held to the first and last rule.
+#![allow(dead_code)]
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    sync::Once,
+};
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        bpf_loader,
+        entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+        instruction::Instruction,
+        program_error::ProgramError,
+        program_pack::Pack,
+        program_stubs,
+        system_instruction::SystemInstruction,
+        system_program,
+    },
+    Discriminator,
+};
+use anchor_spl::{associated_token, token::spl_token};
+
+pub mod setup;
+pub mod switchboard;
+
+const NATIVE_LOADER_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("NativeLoader1111111111111111111111111111111");
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Account {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+impl Account {
+    pub fn new(lamports: u64, data: Vec<u8>, owner: Pubkey) -> Self {
+        Self {
+            lamports,
+            data,
+            owner,
+            executable: false,
+        }
+    }
+}
+
+// Live state of the running transaction, the syscall stubs are global so it lives per thread
+#[derive(Default)]
+struct Runtime {
+    clock: Clock,
+    epoch_schedule: EpochSchedule,
+    program_stack: Vec<Pubkey>,
+    // Accounts of each running instruction as they were before it, or its last CPI, ran
+    frames: Vec<Vec<PreAccount>>,
+    // Backing buffers so the system program can grow account data in place
+    buffers: HashMap<Pubkey, (*mut u8, usize)>,
+    executables: HashMap<Pubkey, Account>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+    logs: Vec<String>,
+}
+
+thread_local! {
+    static RUNTIME: RefCell<Runtime> = RefCell::new(Runtime::default());
+}
+
+struct Stubs;
+
+impl program_stubs::SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        RUNTIME.with(|rt| rt.borrow_mut().logs.push(message.to_string()));
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let encoded: Vec<String> = fields
+            .iter()
+            .map(anchor_lang::__private::base64::encode)
+            .collect();
+        self.sol_log(&format!("Program data: {}", encoded.join(" ")));
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        invoke_signed(instruction, account_infos, signers_seeds)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = RUNTIME.with(|rt| rt.borrow().clock.clone());
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let epoch_schedule = RUNTIME.with(|rt| rt.borrow().epoch_schedule);
+        unsafe { *(var_addr as *mut EpochSchedule) = epoch_schedule };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        RUNTIME.with(|rt| rt.borrow().return_data.clone())
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        RUNTIME.with(|rt| {
+            let mut rt = rt.borrow_mut();
+            let program_id = *rt.program_stack.last().unwrap();
+            rt.return_data = Some((program_id, data.to_vec()));
+        });
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        RUNTIME.with(|rt| rt.borrow().program_stack.len() as u64)
+    }
+}
+
+fn leak<T>(value: T) -> &'static mut T {
+    Box::leak(Box::new(value))
+}
+
+fn current_program() -> Pubkey {
+    RUNTIME.with(|rt| *rt.borrow().program_stack.last().unwrap())
+}
+
+pub fn log(message: String) {
+    RUNTIME.with(|rt| rt.borrow_mut().logs.push(message));
+}
+
+// Same layout the loader serializes: `AccountInfo::original_data_len` reads the u32 right
+// before the key and `AccountInfo::realloc` writes the new length into the u64 before the data
+#[repr(C)]
+struct KeySlot {
+    original_data_len: u32,
+    key: Pubkey,
+}
+
+/// Builds an AccountInfo over leaked storage, test processes are short lived.
+fn account_info(key: Pubkey, account: &Account, track: bool) -> AccountInfo<'static> {
+    let len = account.data.len();
+    let capacity = len + MAX_PERMITTED_DATA_INCREASE;
+    let buffer: &'static mut [u8] = Box::leak(vec![0u8; 8 + capacity].into_boxed_slice());
+    buffer[..8].copy_from_slice(&(len as u64).to_le_bytes());
+    let buffer = &mut buffer[8..];
+    buffer[..len].copy_from_slice(&account.data);
+    let ptr = buffer.as_mut_ptr();
+    if track {
+        RUNTIME.with(|rt| rt.borrow_mut().buffers.insert(key, (ptr, capacity)));
+    }
+    let slot = leak(KeySlot {
+        original_data_len: len as u32,
+        key,
+    });
+    AccountInfo::new(
+        &slot.key,
+        false,
+        false,
+        leak(account.lamports),
+        &mut buffer[..len],
+        leak(account.owner),
+        account.executable,
+        0,
+    )
+}
+
+/// Grows or shrinks the data of an account created by this runtime, new bytes are zeroed.
+pub fn resize(info: &AccountInfo, new_len: usize) -> ProgramResult {
+    let (ptr, capacity) = RUNTIME
+        .with(|rt| rt.borrow().buffers.get(info.key).copied())
+        .ok_or(ProgramError::InvalidRealloc)?;
+    if new_len > capacity {
+        return Err(ProgramError::InvalidRealloc);
+    }
+    let mut data = info.try_borrow_mut_data()?;
+    let old_len = data.len();
+    *data = unsafe { std::slice::from_raw_parts_mut(ptr, new_len) };
+    if new_len > old_len {
+        data[old_len..].fill(0);
+    }
+    Ok(())
+}
+
+/// An account as a running instruction found it, through the AccountInfo it still holds.
+struct PreAccount {
+    info: AccountInfo<'static>,
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+}
+
+impl PreAccount {
+    fn new(info: &AccountInfo<'static>) -> Self {
+        Self {
+            info: info.clone(),
+            lamports: info.lamports(),
+            data: info.data.borrow().to_vec(),
+            owner: *info.owner,
+        }
+    }
+}
+
+/// One entry per key, privileges are already merged.
+fn pre_accounts(infos: &[AccountInfo<'static>]) -> Vec<PreAccount> {
+    let mut seen = HashSet::new();
+    infos
+        .iter()
+        .filter(|info| !info.executable && seen.insert(*info.key))
+        .map(PreAccount::new)
+        .collect()
+}
+
+/// Checks what `program_id` did to the accounts since `pre`, same rules as the runtime.
+fn verify_accounts(program_id: &Pubkey, pre: &[PreAccount]) -> ProgramResult {
+    let (mut pre_lamports, mut post_lamports) = (0u128, 0u128);
+    for pre in pre {
+        let info = &pre.info;
+        let (lamports, owner, data) = (info.lamports(), *info.owner, info.data.borrow());
+        pre_lamports += pre.lamports as u128;
+        post_lamports += lamports as u128;
+
+        let changed = lamports != pre.lamports || owner != pre.owner || **data != pre.data[..];
+        if changed && !info.is_writable {
+            log(format!(
+                "{} modified read-only account {}",
+                program_id, info.key
+            ));
+            return Err(ProgramError::InvalidArgument);
+        }
+        if pre.owner != *program_id && *program_id != switchboard::ID {
+            if lamports < pre.lamports {
+                log(format!(
+                    "{} debited account {} it does not own",
+                    program_id, info.key
+                ));
+                return Err(ProgramError::IllegalOwner);
+            }
+            if **data != pre.data[..] || owner != pre.owner {
+                log(format!(
+                    "{} modified account {} it does not own",
+                    program_id, info.key
+                ));
+                return Err(ProgramError::IllegalOwner);
+            }
+        }
+        if owner != pre.owner && *program_id != switchboard::ID && data.iter().any(|b| *b != 0) {
+            log(format!(
+                "{} reassigned account {} with data left",
+                program_id, info.key
+            ));
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+    if pre_lamports != post_lamports {
+        log(format!("{} left the lamports unbalanced", program_id));
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Runs `program_id` over `infos` and checks the accounts it changed.
+fn run_verified(
+    program_id: &Pubkey,
+    infos: &'static [AccountInfo<'static>],
+    run: impl FnOnce() -> ProgramResult,
+) -> ProgramResult {
+    RUNTIME.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        rt.program_stack.push(*program_id);
+        rt.frames.push(pre_accounts(infos));
+    });
+    let result = run();
+    let pre = RUNTIME.with(|rt| {
+        let mut rt = rt.borrow_mut();
+        rt.program_stack.pop();
+        rt.frames.pop().unwrap()
+    });
+    result?;
+    verify_accounts(program_id, &pre)
+}
+
+/// Checks the caller's changes before a CPI and takes the callee's as the new baseline.
+fn sync_caller(caller: &Pubkey, verify: bool) -> ProgramResult {
+    let pre = RUNTIME.with(|rt| std::mem::take(rt.borrow_mut().frames.last_mut().unwrap()));
+    let result = if verify {
+        verify_accounts(caller, &pre)
+    } else {
+        Ok(())
+    };
+    let synced = pre.iter().map(|pre| PreAccount::new(&pre.info)).collect();
+    RUNTIME.with(|rt| *rt.borrow_mut().frames.last_mut().unwrap() = synced);
+    result
+}
+
+/// The runtime hands every occurrence of a key the union of its privileges.
+fn merge_privileges(metas: &[AccountMeta]) -> Vec<AccountMeta> {
+    metas
+        .iter()
+        .map(|meta| AccountMeta {
+            pubkey: meta.pubkey,
+            is_signer: metas
+                .iter()
+                .any(|other| other.pubkey == meta.pubkey && other.is_signer),
+            is_writable: metas
+                .iter()
+                .any(|other| other.pubkey == meta.pubkey && other.is_writable),
+        })
+        .collect()
+}
+
+fn invoke_signed(
+    instruction: &Instruction,
+    account_infos: &[AccountInfo],
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let caller = current_program();
+    let pda_signers = signers_seeds
+        .iter()
+        .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+        .collect::<std::result::Result<HashSet<_>, _>>()
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+
+    // Every AccountInfo handed to a program is built over leaked storage by this runtime
+    let account_infos: &[AccountInfo<'static>] = unsafe { std::mem::transmute(account_infos) };
+    let mut infos = Vec::with_capacity(instruction.accounts.len());
+    for meta in merge_privileges(&instruction.accounts) {
+        let mut info = match account_infos.iter().find(|info| info.key == &meta.pubkey) {
+            Some(info) => info.clone(),
+            // Programs do not have to be passed along, same as the real runtime
+            None => match RUNTIME.with(|rt| rt.borrow().executables.get(&meta.pubkey).cloned()) {
+                Some(program) => account_info(meta.pubkey, &program, false),
+                None => {
+                    log(format!(
+                        "Instruction references an unknown account {}",
+                        meta.pubkey
+                    ));
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                }
+            },
+        };
+        if meta.is_writable && !info.is_writable {
+            log(format!("{}'s writable privilege escalated", meta.pubkey));
+            return Err(ProgramError::InvalidArgument);
+        }
+        if meta.is_signer && !info.is_signer && !pda_signers.contains(&meta.pubkey) {
+            log(format!("{}'s signer privilege escalated", meta.pubkey));
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        info.is_writable = meta.is_writable;
+        info.is_signer = meta.is_signer;
+        infos.push(info);
+    }
+    let infos: &'static [AccountInfo<'static>] = Box::leak(infos.into_boxed_slice());
+
+    sync_caller(&caller, true)?;
+    let result = run_verified(&instruction.program_id, infos, || {
+        process_builtin(&instruction.program_id, infos, &instruction.data)
+    });
+    sync_caller(&caller, false)?;
+    result
+}
+
+fn process_builtin(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    log(format!("Program {} invoke", program_id));
+    if *program_id == system_program::ID {
+        process_system(accounts, data)
+    } else if *program_id == spl_token::ID {
+        spl_token::processor::Processor::process(program_id, accounts, data)
+    } else if *program_id == spl_token_2022::ID {
+        spl_token_2022::processor::Processor::process(program_id, accounts, data)
+    } else if *program_id == associated_token::ID {
+        spl_associated_token_account::processor::process_instruction(program_id, accounts, data)
+    } else if *program_id == switchboard::ID {
+        switchboard::process(accounts, data)
+    } else {
+        log(format!("Program {} is not supported in CPI", program_id));
+        Err(ProgramError::IncorrectProgramId)
+    }
+}
+
+fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *from.owner != system_program::ID || !from.data_is_empty() {
+        log("Transfer: `from` must not carry data".to_string());
+        return Err(ProgramError::InvalidArgument);
+    }
+    let balance = from.lamports();
+    if balance < lamports {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    **from.try_borrow_mut_lamports()? = balance - lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+fn allocate_and_assign(account: &AccountInfo, space: u64, owner: &Pubkey) -> ProgramResult {
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *account.owner != system_program::ID || !account.data_is_empty() {
+        log(format!("Allocate: account {} already in use", account.key));
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    resize(account, space as usize)?;
+    account.assign(owner);
+    Ok(())
+}
+
+fn process_system(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction: SystemInstruction =
+        bincode::deserialize(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    match instruction {
+        SystemInstruction::CreateAccount {
+            lamports,
+            space,
+            owner,
+        } => {
+            if accounts[1].lamports() > 0 {
+                log(format!(
+                    "Create Account: account {} already in use",
+                    accounts[1].key
+                ));
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            allocate_and_assign(&accounts[1], space, &owner)?;
+            transfer_lamports(&accounts[0], &accounts[1], lamports)
+        }
+        SystemInstruction::Transfer { lamports } => {
+            transfer_lamports(&accounts[0], &accounts[1], lamports)
+        }
+        SystemInstruction::Allocate { space } => {
+            allocate_and_assign(&accounts[0], space, &system_program::ID)
+        }
+        SystemInstruction::Assign { owner } => {
+            if !accounts[0].is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            accounts[0].assign(&owner);
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+pub struct ProgramTest {
+    accounts: HashMap<Pubkey, Account>,
+    pub clock: Clock,
+    /// Mainnet's 432,000 slots per epoch, without warmup.
+    pub epoch_schedule: EpochSchedule,
+    logs: Vec<String>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+}
+
+impl ProgramTest {
+    pub fn new() -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            program_stubs::set_syscall_stubs(Box::new(Stubs));
+        });
+
+        let mut test = Self {
+            accounts: HashMap::new(),
+            clock: Clock {
+                slot: 1_000,
+                epoch: 1,
+                unix_timestamp: 1_700_000_000,
+                ..Clock::default()
+            },
+            epoch_schedule: EpochSchedule::without_warmup(),
+            logs: vec![],
+            return_data: None,
+        };
+        for program_id in [
+            l2::ID,
+            spl_token::ID,
+            spl_token_2022::ID,
+            associated_token::ID,
+            switchboard::ID,
+        ] {
+            test.add_program(program_id, bpf_loader::ID);
+        }
+        test.add_program(system_program::ID, NATIVE_LOADER_ID);
+
+        let mut native_mint = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            decimals: spl_token::native_mint::DECIMALS,
+            is_initialized: true,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut native_mint);
+        test.set_account(
+            spl_token::native_mint::ID,
+            Account::new(test.rent(native_mint.len()), native_mint, spl_token::ID),
+        );
+        test
+    }
+
+    fn add_program(&mut self, program_id: Pubkey, loader: Pubkey) {
+        self.accounts.insert(
+            program_id,
+            Account {
+                lamports: 1,
+                data: vec![],
+                owner: loader,
+                executable: true,
+            },
+        );
+    }
+
+    pub fn rent(&self, len: usize) -> u64 {
+        Rent::default().minimum_balance(len)
+    }
+
+    pub fn set_account(&mut self, key: Pubkey, account: Account) {
+        self.accounts.insert(key, account);
+    }
+
+    pub fn account(&self, key: &Pubkey) -> Option<&Account> {
+        self.accounts.get(key)
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.account(key)
+            .map(|account| account.lamports)
+            .unwrap_or_default()
+    }
+
+    pub fn airdrop(&mut self, key: &Pubkey, lamports: u64) {
+        self.accounts
+            .entry(*key)
+            .or_insert_with(|| Account::new(0, vec![], system_program::ID))
+            .lamports += lamports;
+    }
+
+    pub fn anchor_account<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+        let account = self.account(key).expect("account not found");
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Moves the clock forward, slots follow at 400ms each.
+    pub fn warp(&mut self, seconds: i64) {
+        self.clock.unix_timestamp += seconds;
+        self.clock.slot += (seconds as u64 * 1000) / 400;
+    }
+
+    pub fn warp_epoch(&mut self, epochs: u64) {
+        self.clock.epoch += epochs;
+    }
+
+    /// Logs of the last processed transaction.
+    pub fn logs(&self) -> &[String] {
+        &self.logs
+    }
+
+    /// Return data left by the last processed transaction.
+    pub fn return_data<T: AnchorDeserialize>(&self) -> Option<T> {
+        let (_, data) = self.return_data.as_ref()?;
+        T::deserialize(&mut &data[..]).ok()
+    }
+
+    /// Anchor events emitted by the last processed transaction.
+    pub fn events<E: AnchorDeserialize + Discriminator>(&self) -> Vec<E> {
+        self.logs
+            .iter()
+            .filter_map(|log| log.strip_prefix("Program data: "))
+            .filter_map(|data| anchor_lang::__private::base64::decode(data).ok())
+            .filter(|data| data.starts_with(&E::DISCRIMINATOR))
+            .map(|data| E::deserialize(&mut &data[8..]).unwrap())
+            .collect()
+    }
+
+    /// Runs the instructions atomically, `signers` stand in for transaction signatures.
+    pub fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[Pubkey],
+    ) -> std::result::Result<(), ProgramError> {
+        let snapshot = self.accounts.clone();
+        let executables = self
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.executable)
+            .map(|(key, account)| (*key, account.clone()))
+            .collect();
+        RUNTIME.with(|rt| {
+            *rt.borrow_mut() = Runtime {
+                clock: self.clock.clone(),
+                epoch_schedule: self.epoch_schedule,
+                executables,
+                ..Runtime::default()
+            }
+        });
+
+        let result = instructions
+            .iter()
+            .try_for_each(|instruction| self.process_instruction(instruction, signers));
+        self.logs = RUNTIME.with(|rt| std::mem::take(&mut rt.borrow_mut().logs));
+        self.return_data = RUNTIME.with(|rt| rt.borrow_mut().return_data.take());
+        if result.is_err() {
+            if std::env::var_os("L2_TEST_LOGS").is_some() {
+                eprintln!("{}", self.logs.join("\n"));
+            }
+            self.accounts = snapshot;
+        }
+        result
+    }
+
+    fn process_instruction(
+        &mut self,
+        instruction: &Instruction,
+        signers: &[Pubkey],
+    ) -> std::result::Result<(), ProgramError> {
+        let mut slots: HashMap<Pubkey, AccountInfo<'static>> = HashMap::new();
+        let mut infos = Vec::with_capacity(instruction.accounts.len());
+        for meta in merge_privileges(&instruction.accounts) {
+            if meta.is_signer && !signers.contains(&meta.pubkey) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            let slot = slots.entry(meta.pubkey).or_insert_with(|| {
+                let account = self
+                    .accounts
+                    .get(&meta.pubkey)
+                    .cloned()
+                    .unwrap_or_else(|| Account::new(0, vec![], system_program::ID));
+                account_info(meta.pubkey, &account, true)
+            });
+            let mut info = slot.clone();
+            info.is_signer = meta.is_signer;
+            info.is_writable = meta.is_writable;
+            infos.push(info);
+        }
+        let infos: &'static [AccountInfo<'static>] = Box::leak(infos.into_boxed_slice());
+
+        run_verified(&instruction.program_id, infos, || {
+            if instruction.program_id == l2::ID {
+                log(format!("Program {} invoke", instruction.program_id));
+                l2::entry(&instruction.program_id, infos, &instruction.data)
+            } else {
+                process_builtin(&instruction.program_id, infos, &instruction.data)
+            }
+        })?;
+
+        for (key, info) in slots {
+            if info.executable {
+                continue;
+            }
+            if info.lamports() == 0 {
+                self.accounts.remove(&key);
+                continue;
+            }
+            self.accounts.insert(
+                key,
+                Account::new(info.lamports(), info.data.borrow().to_vec(), *info.owner),
+            );
+        }
+        Ok(())
+    }
+}
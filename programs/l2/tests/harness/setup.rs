@@ -0,0 +1,412 @@
+//! A funded payer, a transfer fee mint handed to its mint manager and the Switchboard
+//! accounts around it. Instructions come from `l2_client` with the environment's accounts.
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program_pack::Pack, system_instruction},
+};
+use anchor_spl::token::spl_token;
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account,
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{self, TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    state,
+};
+
+use l2::{
+    FeeDestination, FeeTier, MaxFee, MintManager, RandomnessRequestError, TriggerUpdateParams,
+    TRIGGER_UPDATE_VERSION,
+};
+use l2_client::SwitchboardAccounts;
+
+use super::{switchboard, ProgramTest};
+
+pub const DECIMALS: u8 = 6;
+pub const INITIAL_FEE_BP: u16 = 100;
+pub const INITIAL_MAX_FEE: u64 = 1_000_000 * 10u64.pow(DECIMALS as u32);
+
+pub fn l2_error(error: RandomnessRequestError) -> ProgramError {
+    ProgramError::Custom(error.into())
+}
+
+pub fn anchor_error(error: anchor_lang::error::ErrorCode) -> ProgramError {
+    ProgramError::Custom(error.into())
+}
+
+pub fn mint_manager_address(mint: &Pubkey) -> Pubkey {
+    l2_client::mint_manager(mint).0
+}
+
+pub fn token_2022_ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &spl_token_2022::ID)
+}
+
+/// Passes `to` wherever the instruction expects `from`.
+pub fn swap_account(ix: &mut Instruction, from: &Pubkey, to: &Pubkey) {
+    for meta in ix.accounts.iter_mut().filter(|meta| meta.pubkey == *from) {
+        meta.pubkey = *to;
+    }
+}
+
+/// Bytes [0-8] pick the fee tier, bytes [8-16] the interval.
+pub fn randomness(fee_random: u64, interval_random: u64) -> TriggerUpdateParams {
+    let mut randomness = [0u8; 32];
+    randomness[0..8].copy_from_slice(&fee_random.to_le_bytes());
+    randomness[8..16].copy_from_slice(&interval_random.to_le_bytes());
+    TriggerUpdateParams {
+        version: TRIGGER_UPDATE_VERSION,
+        randomness,
+    }
+}
+
+pub struct Env {
+    pub test: ProgramTest,
+    pub payer: Pubkey,
+    pub mint: Pubkey,
+    pub mint_manager: Pubkey,
+    pub switchboard_state: Pubkey,
+    pub attestation_queue: Pubkey,
+    pub function: Pubkey,
+    pub request: Pubkey,
+    pub escrow: Pubkey,
+    pub enclave_signer: Pubkey,
+}
+
+impl Env {
+    /// Mint created with its fee authorities on the manager PDA, nothing initialized yet.
+    pub fn new() -> Self {
+        let mut test = ProgramTest::new();
+        let payer = Pubkey::new_unique();
+        test.airdrop(&payer, 100_000_000_000);
+        let (switchboard_state, attestation_queue, function) = switchboard::add_accounts(&mut test);
+        let request = Pubkey::new_unique();
+
+        let mut env = Self {
+            test,
+            payer,
+            mint: Pubkey::default(),
+            mint_manager: Pubkey::default(),
+            switchboard_state,
+            attestation_queue,
+            function,
+            request,
+            escrow: get_associated_token_address(&request, &spl_token::native_mint::ID),
+            enclave_signer: Pubkey::new_unique(),
+        };
+        let mint = Pubkey::new_unique();
+        let mint_manager = mint_manager_address(&mint);
+        env.create_mint(&mint, Some(&mint_manager), Some(&mint_manager));
+        env.mint = mint;
+        env.mint_manager = mint_manager;
+        env
+    }
+
+    /// Manager initialized with a one hour delay and its token account ready for collections.
+    pub fn new_initialized() -> Self {
+        let mut env = Self::new();
+        let ix = env.init_global(&env.mint, 1);
+        env.test.process(&[ix], &[env.payer, env.request]).unwrap();
+        env.create_ata(&env.mint_manager.clone());
+        env
+    }
+
+    pub fn mint_manager(&self) -> MintManager {
+        self.test.anchor_account(&self.mint_manager)
+    }
+
+    // TOKEN-2022 SETUP
+
+    /// Transfer fee mint, `None` authorities leave the extension without them.
+    pub fn create_mint(
+        &mut self,
+        mint: &Pubkey,
+        transfer_fee_config_authority: Option<&Pubkey>,
+        withdraw_withheld_authority: Option<&Pubkey>,
+    ) {
+        let space = ExtensionType::try_calculate_account_len::<state::Mint>(&[
+            ExtensionType::TransferFeeConfig,
+        ])
+        .unwrap();
+        let ixs = [
+            system_instruction::create_account(
+                &self.payer,
+                mint,
+                self.test.rent(space),
+                space as u64,
+                &spl_token_2022::ID,
+            ),
+            transfer_fee::instruction::initialize_transfer_fee_config(
+                &spl_token_2022::ID,
+                mint,
+                transfer_fee_config_authority,
+                withdraw_withheld_authority,
+                INITIAL_FEE_BP,
+                INITIAL_MAX_FEE,
+            )
+            .unwrap(),
+            spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::ID,
+                mint,
+                &self.payer,
+                None,
+                DECIMALS,
+            )
+            .unwrap(),
+        ];
+        self.test.process(&ixs, &[self.payer, *mint]).unwrap();
+    }
+
+    /// Token-2022 mint without any extension.
+    pub fn create_plain_mint(&mut self, mint: &Pubkey) {
+        let space = state::Mint::LEN;
+        let ixs = [
+            system_instruction::create_account(
+                &self.payer,
+                mint,
+                self.test.rent(space),
+                space as u64,
+                &spl_token_2022::ID,
+            ),
+            spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::ID,
+                mint,
+                &self.payer,
+                None,
+                DECIMALS,
+            )
+            .unwrap(),
+        ];
+        self.test.process(&ixs, &[self.payer, *mint]).unwrap();
+    }
+
+    pub fn create_ata(&mut self, owner: &Pubkey) -> Pubkey {
+        let ix =
+            create_associated_token_account(&self.payer, owner, &self.mint, &spl_token_2022::ID);
+        self.test.process(&[ix], &[self.payer]).unwrap();
+        token_2022_ata(owner, &self.mint)
+    }
+
+    pub fn mint_to(&mut self, owner: &Pubkey, amount: u64) {
+        let ix = spl_token_2022::instruction::mint_to(
+            &spl_token_2022::ID,
+            &self.mint,
+            &token_2022_ata(owner, &self.mint),
+            &self.payer,
+            &[],
+            amount,
+        )
+        .unwrap();
+        self.test.process(&[ix], &[self.payer]).unwrap();
+    }
+
+    /// The mint withholds its transfer fee on the destination account.
+    pub fn transfer(&mut self, from: &Pubkey, to: &Pubkey, amount: u64) {
+        let ix = spl_token_2022::instruction::transfer_checked(
+            &spl_token_2022::ID,
+            &token_2022_ata(from, &self.mint),
+            &self.mint,
+            &token_2022_ata(to, &self.mint),
+            from,
+            &[],
+            amount,
+            DECIMALS,
+        )
+        .unwrap();
+        self.test.process(&[ix], &[self.payer, *from]).unwrap();
+    }
+
+    /// Wraps SOL into the request escrow.
+    pub fn fund_escrow(&mut self, lamports: u64) {
+        let ixs = [
+            system_instruction::transfer(&self.payer, &self.escrow, lamports),
+            spl_token::instruction::sync_native(&spl_token::ID, &self.escrow).unwrap(),
+        ];
+        self.test.process(&ixs, &[self.payer]).unwrap();
+    }
+
+    pub fn balance(&self, token_account: &Pubkey) -> u64 {
+        let data = &self.test.account(token_account).unwrap().data;
+        StateWithExtensions::<state::Account>::unpack(data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    pub fn withheld(&self, token_account: &Pubkey) -> u64 {
+        let data = &self.test.account(token_account).unwrap().data;
+        let account = StateWithExtensions::<state::Account>::unpack(data).unwrap();
+        account
+            .get_extension::<TransferFeeAmount>()
+            .unwrap()
+            .withheld_amount
+            .into()
+    }
+
+    pub fn supply(&self) -> u64 {
+        let data = &self.test.account(&self.mint).unwrap().data;
+        StateWithExtensions::<state::Mint>::unpack(data)
+            .unwrap()
+            .base
+            .supply
+    }
+
+    pub fn transfer_fee_config(&self) -> TransferFeeConfig {
+        let data = &self.test.account(&self.mint).unwrap().data;
+        *StateWithExtensions::<state::Mint>::unpack(data)
+            .unwrap()
+            .get_extension::<TransferFeeConfig>()
+            .unwrap()
+    }
+
+    // L2 INSTRUCTIONS
+
+    pub fn switchboard(&self) -> SwitchboardAccounts {
+        SwitchboardAccounts {
+            attestation_queue: self.attestation_queue,
+            function: self.function,
+            request: self.request,
+        }
+    }
+
+    pub fn init_global(&self, mint: &Pubkey, hour_to_next_update: u8) -> Instruction {
+        l2_client::init_global(mint, &self.payer, &self.switchboard(), hour_to_next_update)
+    }
+
+    pub fn trigger_update(&self, params: TriggerUpdateParams) -> Instruction {
+        l2_client::trigger_update(
+            &self.mint,
+            &self.enclave_signer,
+            &self.switchboard(),
+            params,
+        )
+    }
+
+    pub fn retrigger_update(&self, payer: &Pubkey) -> Instruction {
+        l2_client::retrigger_update(&self.mint, payer, &self.switchboard())
+    }
+
+    pub fn deposit_escrow(&self, depositor: &Pubkey, amount: u64) -> Instruction {
+        l2_client::deposit_escrow(&self.mint, depositor, &self.switchboard(), amount)
+    }
+
+    pub fn escrow_runway(&self) -> Instruction {
+        l2_client::escrow_runway(&self.mint, &self.switchboard())
+    }
+
+    /// Points the manager at `function` with `request` as its new request.
+    pub fn set_switchboard_function(
+        &self,
+        authority: &Pubkey,
+        attestation_queue: &Pubkey,
+        function: &Pubkey,
+        request: &Pubkey,
+    ) -> Instruction {
+        let new = SwitchboardAccounts {
+            attestation_queue: *attestation_queue,
+            function: *function,
+            request: *request,
+        };
+        l2_client::set_switchboard_function(&self.mint, authority, &self.switchboard(), &new)
+    }
+
+    pub fn collect_and_burn_from_account(
+        &self,
+        sources: &[Pubkey],
+        treasury: Option<Pubkey>,
+        rewards_vault: Option<Pubkey>,
+    ) -> Instruction {
+        l2_client::collect_and_burn_from_account(&self.mint, sources, treasury, rewards_vault)
+    }
+
+    pub fn collect_and_burn_from_mint(
+        &self,
+        treasury: Option<Pubkey>,
+        rewards_vault: Option<Pubkey>,
+    ) -> Instruction {
+        l2_client::collect_and_burn_from_mint(&self.mint, treasury, rewards_vault)
+    }
+
+    pub fn harvest_withheld_tokens_to_mint(&self, sources: &[Pubkey]) -> Instruction {
+        l2_client::harvest_withheld_tokens_to_mint(&self.mint, sources)
+    }
+
+    pub fn set_fee_tiers(&self, authority: &Pubkey, fee_tiers: Vec<FeeTier>) -> Instruction {
+        l2_client::set_fee_tiers(&self.mint, authority, fee_tiers)
+    }
+
+    pub fn set_bounty(&self, authority: &Pubkey, bounty: u64) -> Instruction {
+        l2_client::set_bounty(&self.mint, authority, bounty)
+    }
+
+    pub fn set_fee_guardrails(
+        &self,
+        authority: &Pubkey,
+        fee_ceiling_bp: Option<u16>,
+        max_fee_delta_bp: Option<u16>,
+    ) -> Instruction {
+        l2_client::set_fee_guardrails(&self.mint, authority, fee_ceiling_bp, max_fee_delta_bp)
+    }
+
+    pub fn set_paused(&self, authority: &Pubkey, paused: bool) -> Instruction {
+        l2_client::set_paused(&self.mint, authority, paused)
+    }
+
+    pub fn set_fee_override(
+        &self,
+        authority: &Pubkey,
+        transfer_fee_bp: u16,
+        duration_slots: u64,
+    ) -> Instruction {
+        l2_client::set_fee_override(&self.mint, authority, transfer_fee_bp, duration_slots)
+    }
+
+    pub fn set_max_fee(&self, authority: &Pubkey, max_fee: MaxFee) -> Instruction {
+        l2_client::set_max_fee(&self.mint, authority, max_fee)
+    }
+
+    pub fn set_fee_destination(
+        &self,
+        authority: &Pubkey,
+        fee_destination: FeeDestination,
+        treasury: Option<Pubkey>,
+        rewards_vault: Option<Pubkey>,
+    ) -> Instruction {
+        l2_client::set_fee_destination(
+            &self.mint,
+            authority,
+            fee_destination,
+            treasury,
+            rewards_vault,
+        )
+    }
+
+    pub fn set_interval_bounds(
+        &self,
+        authority: &Pubkey,
+        min_interval_secs: i64,
+        max_interval_secs: i64,
+    ) -> Instruction {
+        l2_client::set_interval_bounds(&self.mint, authority, min_interval_secs, max_interval_secs)
+    }
+
+    pub fn set_late_window(&self, authority: &Pubkey, late_window_secs: i64) -> Instruction {
+        l2_client::set_late_window(&self.mint, authority, late_window_secs)
+    }
+
+    pub fn propose_authority(&self, authority: &Pubkey, new_authority: &Pubkey) -> Instruction {
+        l2_client::propose_authority(&self.mint, authority, new_authority)
+    }
+
+    pub fn accept_authority(&self, pending_authority: &Pubkey) -> Instruction {
+        l2_client::accept_authority(&self.mint, pending_authority)
+    }
+
+    pub fn renounce_authority(&self, authority: &Pubkey) -> Instruction {
+        l2_client::renounce_authority(&self.mint, authority)
+    }
+}
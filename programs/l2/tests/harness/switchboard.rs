@@ -0,0 +1,300 @@
+//! Stub of the Switchboard attestation program.
+//!
+//! Only implements the instructions l2 invokes. Account checks mirror the
+//! constraints of the real `FunctionRequestInit` / `FunctionRequestTrigger`
+//! contexts so wrong mut / signer flags fail here the same way they would on chain.
+
+use anchor_lang::{
+    __private::bytemuck::{self, Zeroable},
+    error::ErrorCode,
+    prelude::*,
+    solana_program::{
+        entrypoint::ProgramResult, program_option::COption, program_pack::Pack, system_program,
+    },
+    Discriminator, ZeroCopy,
+};
+use anchor_spl::token::spl_token;
+use switchboard_solana::{
+    AttestationProgramState, AttestationQueueAccountData, FunctionAccountData,
+    FunctionRequestAccountData, FunctionRequestCloseParams, FunctionRequestInitParams,
+    FunctionRequestTriggerParams, RequestStatus,
+};
+
+use super::{log, resize, Account, ProgramTest};
+
+pub use switchboard_solana::SWITCHBOARD_ATTESTATION_PROGRAM_ID as ID;
+
+fn constraint(code: ErrorCode, account: &AccountInfo) -> ProgramError {
+    log(format!(
+        "Switchboard stub: {:?} violated by {}",
+        code, account.key
+    ));
+    ProgramError::Custom(code.into())
+}
+
+fn require_mut(account: &AccountInfo) -> ProgramResult {
+    match account.is_writable {
+        true => Ok(()),
+        false => Err(constraint(ErrorCode::ConstraintMut, account)),
+    }
+}
+
+fn require_signer(account: &AccountInfo) -> ProgramResult {
+    match account.is_signer {
+        true => Ok(()),
+        false => Err(constraint(ErrorCode::ConstraintSigner, account)),
+    }
+}
+
+fn require_empty(account: &AccountInfo) -> ProgramResult {
+    match *account.owner == system_program::ID && account.data_is_empty() && account.lamports() == 0
+    {
+        true => Ok(()),
+        false => Err(constraint(ErrorCode::ConstraintRaw, account)),
+    }
+}
+
+/// Moves rent out of the payer and hands the account to `owner`.
+fn create(
+    payer: &AccountInfo,
+    account: &AccountInfo,
+    data: &[u8],
+    owner: &Pubkey,
+) -> ProgramResult {
+    let lamports = Rent::get()?.minimum_balance(data.len());
+    **payer.try_borrow_mut_lamports()? = payer
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **account.try_borrow_mut_lamports()? = lamports;
+    resize(account, data.len())?;
+    account.try_borrow_mut_data()?.copy_from_slice(data);
+    account.assign(owner);
+    Ok(())
+}
+
+pub fn process(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (discriminator, params) = data.split_at(8);
+    if discriminator == FunctionRequestInitParams::DISCRIMINATOR {
+        request_init(accounts, FunctionRequestInitParams::try_from_slice(params)?)
+    } else if discriminator == FunctionRequestTriggerParams::DISCRIMINATOR {
+        request_trigger(
+            accounts,
+            FunctionRequestTriggerParams::try_from_slice(params)?,
+        )
+    } else if discriminator == FunctionRequestCloseParams::DISCRIMINATOR {
+        request_close(accounts)
+    } else {
+        Err(ProgramError::InvalidInstructionData)
+    }
+}
+
+fn request_init(accounts: &[AccountInfo], params: FunctionRequestInitParams) -> ProgramResult {
+    let [request, authority, function, _function_authority, escrow, mint, _state, attestation_queue, payer, ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    require_mut(request)?;
+    require_signer(request)?;
+    require_empty(request)?;
+    require_mut(function)?;
+    require_mut(escrow)?;
+    require_empty(escrow)?;
+    require_mut(payer)?;
+    require_signer(payer)?;
+    if *mint.key != spl_token::native_mint::ID {
+        return Err(constraint(ErrorCode::ConstraintAddress, mint));
+    }
+
+    let request_data = FunctionRequestAccountData {
+        authority: *authority.key,
+        payer: *payer.key,
+        function: *function.key,
+        escrow: *escrow.key,
+        attestation_queue: *attestation_queue.key,
+        max_container_params_len: params.max_container_params_len.unwrap_or(256),
+        container_params: params.container_params,
+        created_at: Clock::get()?.unix_timestamp,
+        garbage_collection_slot: params.garbage_collection_slot,
+        ..Default::default()
+    };
+    let mut data = vec![];
+    request_data.try_serialize(&mut data)?;
+    create(payer, request, &data, &ID)?;
+
+    let mut escrow_data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: spl_token::native_mint::ID,
+        owner: *request.key,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::Some(Rent::get()?.minimum_balance(escrow_data.len())),
+        ..Default::default()
+    }
+    .pack_into_slice(&mut escrow_data);
+    create(payer, escrow, &escrow_data, &spl_token::ID)
+}
+
+fn request_trigger(
+    accounts: &[AccountInfo],
+    params: FunctionRequestTriggerParams,
+) -> ProgramResult {
+    let [request, authority, escrow, function, _state, _attestation_queue, payer, ..] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    require_mut(request)?;
+    require_signer(authority)?;
+    require_mut(escrow)?;
+    require_mut(function)?;
+    require_mut(payer)?;
+    require_signer(payer)?;
+    if *request.owner != ID {
+        return Err(constraint(ErrorCode::ConstraintOwner, request));
+    }
+
+    let mut request_data =
+        FunctionRequestAccountData::try_deserialize(&mut &request.data.borrow()[..])?;
+    if request_data.authority != *authority.key {
+        return Err(constraint(ErrorCode::ConstraintHasOne, authority));
+    }
+    if request_data.function != *function.key {
+        return Err(constraint(ErrorCode::ConstraintHasOne, function));
+    }
+    if request_data.escrow != *escrow.key {
+        return Err(constraint(ErrorCode::ConstraintHasOne, escrow));
+    }
+
+    let clock = Clock::get()?;
+    request_data.is_triggered = 1;
+    request_data.status = RequestStatus::RequestPending;
+    request_data.active_request.status = RequestStatus::RequestPending;
+    request_data.active_request.bounty = params.bounty.unwrap_or_default();
+    request_data.active_request.request_slot = clock.slot;
+    request_data.active_request.valid_after_slot = params.valid_after_slot.unwrap_or_default();
+    request_data.try_serialize(&mut &mut request.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+/// Hands the lamports to `destination` and leaves the account for the runtime to purge.
+fn close(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    **destination.try_borrow_mut_lamports()? += account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    resize(account, 0)?;
+    account.assign(&system_program::ID);
+    Ok(())
+}
+
+fn request_close(accounts: &[AccountInfo]) -> ProgramResult {
+    let [request, authority, escrow, function, sol_dest, escrow_dest, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    require_mut(request)?;
+    require_signer(authority)?;
+    require_mut(escrow)?;
+    require_mut(function)?;
+    require_mut(sol_dest)?;
+    require_mut(escrow_dest)?;
+    if *request.owner != ID {
+        return Err(constraint(ErrorCode::ConstraintOwner, request));
+    }
+
+    let request_data =
+        FunctionRequestAccountData::try_deserialize(&mut &request.data.borrow()[..])?;
+    if request_data.authority != *authority.key {
+        return Err(constraint(ErrorCode::ConstraintHasOne, authority));
+    }
+    if request_data.function != *function.key {
+        return Err(constraint(ErrorCode::ConstraintHasOne, function));
+    }
+    if request_data.escrow != *escrow.key {
+        return Err(constraint(ErrorCode::ConstraintHasOne, escrow));
+    }
+
+    // Only native accounts of the request authority can receive the escrow
+    let mut dest_data = spl_token::state::Account::unpack(&escrow_dest.data.borrow())?;
+    if !dest_data.is_native() || dest_data.owner != request_data.authority {
+        return Err(constraint(ErrorCode::ConstraintRaw, escrow_dest));
+    }
+    let escrow_data = spl_token::state::Account::unpack(&escrow.data.borrow())?;
+    dest_data.amount += escrow_data.amount;
+    spl_token::state::Account::pack(dest_data, &mut escrow_dest.data.borrow_mut())?;
+    **escrow.try_borrow_mut_lamports()? -= escrow_data.amount;
+    **escrow_dest.try_borrow_mut_lamports()? += escrow_data.amount;
+
+    close(escrow, sol_dest)?;
+    close(request, sol_dest)
+}
+
+fn zero_copy_data<T: ZeroCopy + Owner>(value: &T) -> Vec<u8> {
+    [&T::DISCRIMINATOR[..], bytemuck::bytes_of(value)].concat()
+}
+
+fn add_zero_copy<T: ZeroCopy + Owner>(test: &mut ProgramTest, key: Pubkey, value: &T) {
+    let data = zero_copy_data(value);
+    test.set_account(key, Account::new(test.rent(data.len()), data, T::owner()));
+}
+
+/// Switchboard accounts l2 reads, returns (state, attestation queue, function).
+pub fn add_accounts(test: &mut ProgramTest) -> (Pubkey, Pubkey, Pubkey) {
+    let state = AttestationProgramState::get_pda();
+    add_program_state(test, state);
+    let queue = add_queue(test);
+    let function = add_function(test, &queue);
+    (state, queue, function)
+}
+
+/// The real program state lives at `AttestationProgramState::get_pda()`.
+pub fn add_program_state(test: &mut ProgramTest, key: Pubkey) {
+    add_zero_copy(test, key, &AttestationProgramState::zeroed());
+}
+
+pub fn add_queue(test: &mut ProgramTest) -> Pubkey {
+    let queue = Pubkey::new_unique();
+    add_zero_copy(test, queue, &AttestationQueueAccountData::zeroed());
+    queue
+}
+
+pub fn add_function(test: &mut ProgramTest, queue: &Pubkey) -> Pubkey {
+    let function = Pubkey::new_unique();
+    let mut function_data = FunctionAccountData::zeroed();
+    function_data.attestation_queue = *queue;
+    add_zero_copy(test, function, &function_data);
+    function
+}
+
+/// What a run draws from the escrow besides the bounty.
+pub fn set_run_costs(
+    test: &mut ProgramTest,
+    queue: &Pubkey,
+    reward: u32,
+    function: &Pubkey,
+    requests_fee: u64,
+) {
+    let mut queue_data: AttestationQueueAccountData =
+        bytemuck::pod_read_unaligned(&test.account(queue).unwrap().data[8..]);
+    queue_data.reward = reward;
+    add_zero_copy(test, *queue, &queue_data);
+    let mut function_data: FunctionAccountData =
+        bytemuck::pod_read_unaligned(&test.account(function).unwrap().data[8..]);
+    function_data.requests_fee = requests_fee;
+    add_zero_copy(test, *function, &function_data);
+}
+
+pub fn request(test: &ProgramTest, request: &Pubkey) -> FunctionRequestAccountData {
+    test.anchor_account(request)
+}
+
+/// Plays the oracle verifying the request, afterwards `enclave_signer` may call back into l2.
+pub fn verify_request(test: &mut ProgramTest, request: &Pubkey, enclave_signer: &Pubkey) {
+    let mut account = test.account(request).unwrap().clone();
+    let mut request_data =
+        FunctionRequestAccountData::try_deserialize(&mut &account.data[..]).unwrap();
+    request_data.active_request.enclave_signer = *enclave_signer;
+    request_data.active_request.status = RequestStatus::RequestSuccess;
+    request_data.status = RequestStatus::RequestSuccess;
+    request_data
+        .try_serialize(&mut &mut account.data[..])
+        .unwrap();
+    test.set_account(*request, account);
+}
@@ -0,0 +1,1300 @@
+//! Runs l2 against the real Token-2022 and associated token processors with a stubbed
+//! Switchboard, no validator or network needed. See `harness/mod.rs`.
+
+mod harness;
+
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{error::ErrorCode, prelude::*};
+use anchor_spl::{associated_token::get_associated_token_address, token::spl_token};
+use l2::{
+    AuthorityAccepted, BountyUpdated, EpochFee, EscrowDeposited, EscrowRunway, FeeDestination,
+    FeeGuardrailsSet, FeeOverrideSet, FeeTier, FeeUpdateSkipped, FeeUpdated, FeesCollected,
+    GlobalInitialized, MaxFee, PauseUpdated, RandomnessRequestError, RequestRetriggered,
+    SwitchboardFunctionUpdated, TokensBurned, WithheldHarvested, MAX_INTERVAL_SECS,
+    MIN_SLOT_DURATION_MS, SECONDS_PER_HOUR, TRIGGER_UPDATE_VERSION,
+};
+use spl_associated_token_account::instruction::create_associated_token_account;
+use switchboard_solana::RequestStatus;
+
+use harness::{
+    setup::{
+        anchor_error, l2_error, randomness, swap_account, token_2022_ata, Env, INITIAL_FEE_BP,
+        INITIAL_MAX_FEE,
+    },
+    switchboard,
+};
+
+/// Warps past the scheduled update and lets the oracle verify the request.
+fn ready_for_update(env: &mut Env) {
+    let wait = env.mint_manager().next_update_ts - env.test.clock.unix_timestamp;
+    env.test.warp(wait);
+    switchboard::verify_request(&mut env.test, &env.request, &env.enclave_signer);
+}
+
+/// Warps until the oracle missed its run by the late window, on the clock and in slots.
+fn warp_past_late_window(env: &mut Env) {
+    let mint_manager = env.mint_manager();
+    let late_slots = mint_manager.late_window_secs as u64 * 1000 / MIN_SLOT_DURATION_MS;
+    let slots = mint_manager.valid_after_slot + late_slots + 1 - env.test.clock.slot;
+    // Harness slots are 400ms
+    env.test.warp((slots * 400).div_ceil(1000) as i64);
+}
+
+/// Two holders with `fee_bp` of the transfer withheld on `to`, returns (from, to) owners.
+fn withheld_transfer(env: &mut Env, amount: u64) -> (Pubkey, Pubkey) {
+    let from = Pubkey::new_unique();
+    let to = Pubkey::new_unique();
+    env.create_ata(&from);
+    env.create_ata(&to);
+    env.mint_to(&from, amount);
+    env.transfer(&from, &to, amount);
+    (from, to)
+}
+
+// INIT_GLOBAL
+
+#[test]
+fn test_init_global() {
+    let env = Env::new_initialized();
+    let now = env.test.clock.unix_timestamp;
+
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.mint, env.mint);
+    assert_eq!(mint_manager.authority, Some(env.payer));
+    assert_eq!(mint_manager.pending_authority, None);
+    assert_eq!(mint_manager.next_update_ts, now + SECONDS_PER_HOUR);
+    assert_eq!(mint_manager.update_interval_secs, SECONDS_PER_HOUR);
+    // One epoch of 432,000 slots at 350ms up to the cap
+    assert_eq!(mint_manager.min_interval_secs, 151_200);
+    assert_eq!(mint_manager.max_interval_secs, MAX_INTERVAL_SECS);
+    assert_eq!(mint_manager.late_window_secs, SECONDS_PER_HOUR);
+    assert_eq!(mint_manager.switchboard_function, env.function);
+    assert_eq!(mint_manager.attestation_queue, env.attestation_queue);
+    assert_eq!(
+        mint_manager.attestation_program_state,
+        env.switchboard_state
+    );
+    assert_eq!(mint_manager.current_transfer_fee_bp, INITIAL_FEE_BP);
+    assert_eq!(mint_manager.max_fee, INITIAL_MAX_FEE);
+    assert_eq!(mint_manager.fee_tiers.len(), 7);
+    assert!(mint_manager.fee_destination == FeeDestination::Burn);
+
+    // Request created with the manager as authority and triggered for the first update
+    let request = switchboard::request(&env.test, &env.request);
+    assert_eq!(request.authority, env.mint_manager);
+    assert_eq!(request.escrow, env.escrow);
+    assert_eq!(request.function, env.function);
+    assert_eq!(request.is_triggered, 1);
+    assert_eq!(request.status, RequestStatus::RequestPending);
+    assert_eq!(
+        request.active_request.valid_after_slot,
+        env.test.clock.slot + SECONDS_PER_HOUR as u64 * 1000 / MIN_SLOT_DURATION_MS
+    );
+    let params = container_params::ContainerParams::decode(&request.container_params).unwrap();
+    assert_eq!(params.program_id, l2::ID);
+    assert_eq!(params.mint_manager, env.mint_manager);
+    assert_eq!(params.mint, env.mint);
+    assert_eq!(params.escrow, env.escrow);
+
+    assert_eq!(mint_manager.switchboard_request, Some(env.request));
+    assert_eq!(mint_manager.switchboard_request_escrow, env.escrow);
+}
+
+#[test]
+fn test_init_global_event() {
+    let mut env = Env::new();
+    let ix = env.init_global(&env.mint, 3);
+    env.test.process(&[ix], &[env.payer, env.request]).unwrap();
+
+    let events = env.test.events::<GlobalInitialized>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].mint_manager, env.mint_manager);
+    assert_eq!(events[0].mint, env.mint);
+    assert_eq!(events[0].authority, env.payer);
+    assert_eq!(events[0].switchboard_function, env.function);
+    assert_eq!(events[0].current_transfer_fee_bp, INITIAL_FEE_BP);
+    assert_eq!(
+        events[0].next_update_ts,
+        env.test.clock.unix_timestamp + 3 * SECONDS_PER_HOUR
+    );
+}
+
+#[test]
+fn test_init_global_twice() {
+    let mut env = Env::new_initialized();
+    env.request = Pubkey::new_unique();
+    let ix = env.init_global(&env.mint, 1);
+    assert!(env.test.process(&[ix], &[env.payer, env.request]).is_err());
+}
+
+#[test]
+fn test_init_global_not_mint_authority() {
+    let mut env = Env::new();
+    let attacker = Pubkey::new_unique();
+    env.test.airdrop(&attacker, 10_000_000_000);
+    env.payer = attacker;
+    let ix = env.init_global(&env.mint, 1);
+    assert_eq!(
+        env.test.process(&[ix], &[attacker, env.request]),
+        Err(l2_error(RandomnessRequestError::InvalidMintAuthority))
+    );
+}
+
+#[test]
+fn test_init_global_invalid_mint_owner() {
+    let mut env = Env::new();
+    let ix = env.init_global(&spl_token::native_mint::ID, 1);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer, env.request]),
+        Err(l2_error(RandomnessRequestError::InvalidMintOwner))
+    );
+}
+
+#[test]
+fn test_init_global_invalid_mint() {
+    let mut env = Env::new();
+    let mint = Pubkey::new_unique();
+    env.test.set_account(
+        mint,
+        harness::Account::new(env.test.rent(10), vec![1; 10], spl_token_2022::ID),
+    );
+    let ix = env.init_global(&mint, 1);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer, env.request]),
+        Err(l2_error(RandomnessRequestError::InvalidMint))
+    );
+}
+
+#[test]
+fn test_init_global_missing_transfer_fee_config() {
+    let mut env = Env::new();
+    let mint = Pubkey::new_unique();
+    env.create_plain_mint(&mint);
+    let ix = env.init_global(&mint, 1);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer, env.request]),
+        Err(l2_error(RandomnessRequestError::MissingTransferFeeConfig))
+    );
+}
+
+#[test]
+fn test_init_global_invalid_transfer_fee_config_authority() {
+    let mut env = Env::new();
+    let mint = Pubkey::new_unique();
+    let mint_manager = harness::setup::mint_manager_address(&mint);
+    let payer = env.payer;
+    env.create_mint(&mint, Some(&payer), Some(&mint_manager));
+    let ix = env.init_global(&mint, 1);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer, env.request]),
+        Err(l2_error(
+            RandomnessRequestError::InvalidTransferFeeConfigAuthority
+        ))
+    );
+}
+
+#[test]
+fn test_init_global_invalid_withdraw_withheld_authority() {
+    let mut env = Env::new();
+    let mint = Pubkey::new_unique();
+    let mint_manager = harness::setup::mint_manager_address(&mint);
+    env.create_mint(&mint, Some(&mint_manager), None);
+    let ix = env.init_global(&mint, 1);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer, env.request]),
+        Err(l2_error(
+            RandomnessRequestError::InvalidWithdrawWithheldAuthority
+        ))
+    );
+}
+
+// TRIGGER_UPDATE
+
+#[test]
+fn test_trigger_update() {
+    let mut env = Env::new_initialized();
+    ready_for_update(&mut env);
+    let now = env.test.clock.unix_timestamp;
+    let slot = env.test.clock.slot;
+    let min_interval = env.mint_manager().min_interval_secs;
+
+    // 7 equal tiers of 10% steps, roll 3 lands on 30%
+    let params = randomness(3, 5);
+    let ix = env.trigger_update(params);
+    env.test
+        .process(&[ix], &[env.payer, env.enclave_signer])
+        .unwrap();
+
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.current_transfer_fee_bp, 3_000);
+    assert_eq!(mint_manager.update_interval_secs, min_interval + 5);
+    assert_eq!(mint_manager.next_update_ts, now + min_interval + 5);
+
+    // Token-2022 schedules the new fee two epochs out
+    let config = env.transfer_fee_config();
+    assert_eq!(
+        u16::from(config.newer_transfer_fee.transfer_fee_basis_points),
+        3_000
+    );
+    assert_eq!(
+        u64::from(config.newer_transfer_fee.maximum_fee),
+        INITIAL_MAX_FEE
+    );
+    assert_eq!(
+        u64::from(config.newer_transfer_fee.epoch),
+        env.test.clock.epoch + 2
+    );
+    assert_eq!(
+        u16::from(config.older_transfer_fee.transfer_fee_basis_points),
+        INITIAL_FEE_BP
+    );
+
+    let request = switchboard::request(&env.test, &env.request);
+    assert_eq!(request.status, RequestStatus::RequestPending);
+    assert_eq!(
+        request.active_request.valid_after_slot,
+        slot + (min_interval as u64 + 5) * 1000 / MIN_SLOT_DURATION_MS
+    );
+    assert_eq!(
+        env.mint_manager().valid_after_slot,
+        request.active_request.valid_after_slot
+    );
+
+    let events = env.test.events::<FeeUpdated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].mint, env.mint);
+    assert_eq!(events[0].old_bp, INITIAL_FEE_BP);
+    assert_eq!(events[0].new_bp, 3_000);
+    assert_eq!(events[0].max_fee, INITIAL_MAX_FEE);
+    assert_eq!(events[0].randomness, params.randomness);
+    assert_eq!(events[0].next_update_ts, now + min_interval + 5);
+}
+
+#[test]
+fn test_trigger_update_applies_settings() {
+    let mut env = Env::new_initialized();
+    let authority = env.payer;
+    let ixs = [
+        env.set_fee_tiers(
+            &authority,
+            vec![
+                FeeTier { bp: 250, weight: 0 },
+                FeeTier { bp: 500, weight: 1 },
+            ],
+        ),
+        env.set_max_fee(&authority, MaxFee::WholeTokens(7)),
+        env.set_interval_bounds(&authority, 151_200, 151_200),
+    ];
+    env.test.process(&ixs, &[authority]).unwrap();
+    ready_for_update(&mut env);
+
+    let ix = env.trigger_update(randomness(u64::MAX, u64::MAX));
+    env.test
+        .process(&[ix], &[env.payer, env.enclave_signer])
+        .unwrap();
+
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.current_transfer_fee_bp, 500);
+    assert_eq!(mint_manager.update_interval_secs, 151_200);
+    let config = env.transfer_fee_config();
+    assert_eq!(
+        u16::from(config.newer_transfer_fee.transfer_fee_basis_points),
+        500
+    );
+    assert_eq!(u64::from(config.newer_transfer_fee.maximum_fee), 7_000_000);
+}
+
+#[test]
+fn test_trigger_update_max_interval() {
+    let mut env = Env::new_initialized();
+    let ix = env.set_interval_bounds(&env.payer, MAX_INTERVAL_SECS, MAX_INTERVAL_SECS);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    ready_for_update(&mut env);
+    let now = env.test.clock.unix_timestamp;
+    let slot = env.test.clock.slot;
+
+    let ix = env.trigger_update(randomness(0, u64::MAX));
+    env.test
+        .process(&[ix], &[env.payer, env.enclave_signer])
+        .unwrap();
+    assert_eq!(env.mint_manager().next_update_ts, now + MAX_INTERVAL_SECS);
+    let request = switchboard::request(&env.test, &env.request);
+    assert_eq!(
+        request.active_request.valid_after_slot,
+        slot + MAX_INTERVAL_SECS as u64 * 1000 / MIN_SLOT_DURATION_MS
+    );
+}
+
+#[test]
+fn test_next_update_overflow() {
+    let mut env = Env::new();
+    env.test.clock.unix_timestamp = i64::MAX - SECONDS_PER_HOUR + 1;
+    let ix = env.init_global(&env.mint, 1);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer, env.request]),
+        Err(l2_error(RandomnessRequestError::NextUpdateOverflow))
+    );
+
+    let mut env = Env::new_initialized();
+    ready_for_update(&mut env);
+    // Even the shortest interval runs past i64::MAX
+    env.test.clock.unix_timestamp = i64::MAX - env.mint_manager().min_interval_secs + 1;
+    let ix = env.trigger_update(randomness(0, 0));
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer, env.enclave_signer]),
+        Err(l2_error(RandomnessRequestError::NextUpdateOverflow))
+    );
+}
+
+#[test]
+fn test_trigger_update_tracks_fee_schedule() {
+    let mut env = Env::new_initialized();
+    let trigger = |env: &mut Env, roll: u64| {
+        ready_for_update(env);
+        let ix = env.trigger_update(randomness(roll, 0));
+        env.test
+            .process(&[ix], &[env.payer, env.enclave_signer])
+            .unwrap();
+    };
+    let assert_mirrors_mint = |env: &Env| {
+        let mint_manager = env.mint_manager();
+        let config = env.transfer_fee_config();
+        assert_eq!(
+            mint_manager.older_transfer_fee,
+            EpochFee::from(&config.older_transfer_fee)
+        );
+        assert_eq!(
+            mint_manager.newer_transfer_fee,
+            EpochFee::from(&config.newer_transfer_fee)
+        );
+        let epoch = env.test.clock.epoch;
+        for epoch in epoch..epoch + 3 {
+            assert_eq!(
+                mint_manager.calculate_fee(1_000_000, epoch),
+                config.calculate_epoch_fee(epoch, 1_000_000)
+            );
+        }
+    };
+    assert_mirrors_mint(&env);
+
+    // 30% is only charged two epochs out, until then the initial fee applies
+    trigger(&mut env, 3);
+    assert_mirrors_mint(&env);
+    let epoch = env.test.clock.epoch;
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.current_transfer_fee_bp, 3_000);
+    assert_eq!(
+        mint_manager.effective_fee(epoch).transfer_fee_bp,
+        INITIAL_FEE_BP
+    );
+    assert_eq!(mint_manager.effective_fee(epoch + 2).transfer_fee_bp, 3_000);
+
+    // Same epoch, the older fee stays put
+    trigger(&mut env, 1);
+    assert_mirrors_mint(&env);
+    assert_eq!(
+        env.mint_manager().effective_fee(epoch).transfer_fee_bp,
+        INITIAL_FEE_BP
+    );
+
+    // Once the newer fee is active it becomes the older one
+    env.test.warp_epoch(2);
+    trigger(&mut env, 5);
+    assert_mirrors_mint(&env);
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.older_transfer_fee.transfer_fee_bp, 1_000);
+    assert_eq!(mint_manager.newer_transfer_fee.transfer_fee_bp, 5_000);
+    assert_eq!(mint_manager.newer_transfer_fee.epoch, epoch + 4);
+}
+
+#[test]
+fn test_trigger_update_not_ready() {
+    let mut env = Env::new_initialized();
+    switchboard::verify_request(&mut env.test, &env.request, &env.enclave_signer);
+    env.test.warp(SECONDS_PER_HOUR - 1);
+
+    let ix = env.trigger_update(randomness(0, 0));
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer, env.enclave_signer]),
+        Err(l2_error(RandomnessRequestError::RequestNotReady))
+    );
+}
+
+#[test]
+fn test_trigger_update_unverified_signer() {
+    let mut env = Env::new_initialized();
+    ready_for_update(&mut env);
+    env.enclave_signer = Pubkey::new_unique();
+
+    let ix = env.trigger_update(randomness(0, 0));
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer, env.enclave_signer]),
+        Err(anchor_error(ErrorCode::ConstraintRaw))
+    );
+}
+
+#[test]
+fn test_trigger_update_unsupported_params_version() {
+    let mut env = Env::new_initialized();
+    ready_for_update(&mut env);
+
+    let mut params = randomness(0, 0);
+    params.version = TRIGGER_UPDATE_VERSION + 1;
+    let ix = env.trigger_update(params);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer, env.enclave_signer]),
+        Err(l2_error(RandomnessRequestError::UnsupportedParamsVersion))
+    );
+}
+
+/// Triggers with one Switchboard account swapped for another of the same type.
+fn trigger_with(
+    swap: impl FnOnce(&mut Env) -> (Pubkey, Pubkey),
+) -> std::result::Result<(), ProgramError> {
+    let mut env = Env::new_initialized();
+    ready_for_update(&mut env);
+    let (from, to) = swap(&mut env);
+    let mut ix = env.trigger_update(randomness(0, 0));
+    swap_account(&mut ix, &from, &to);
+    env.test.process(&[ix], &[env.payer, env.enclave_signer])
+}
+
+#[test]
+fn test_trigger_update_stored_switchboard_accounts() {
+    // A verified copy of the request passes validate_request, only the stored key rejects it
+    assert_eq!(
+        trigger_with(|env| {
+            let copy = Pubkey::new_unique();
+            let account = env.test.account(&env.request).unwrap().clone();
+            env.test.set_account(copy, account);
+            (env.request, copy)
+        }),
+        Err(l2_error(
+            RandomnessRequestError::IncorrectSwitchboardRequest
+        ))
+    );
+    assert_eq!(
+        trigger_with(|env| {
+            let function = switchboard::add_function(&mut env.test, &env.attestation_queue);
+            (env.function, function)
+        }),
+        Err(l2_error(
+            RandomnessRequestError::IncorrectSwitchboardFunction
+        ))
+    );
+    assert_eq!(
+        trigger_with(|env| (env.attestation_queue, switchboard::add_queue(&mut env.test))),
+        Err(l2_error(RandomnessRequestError::IncorrectAttestationQueue))
+    );
+    assert_eq!(
+        trigger_with(|env| {
+            let state = Pubkey::new_unique();
+            switchboard::add_program_state(&mut env.test, state);
+            (env.switchboard_state, state)
+        }),
+        Err(l2_error(RandomnessRequestError::IncorrectSwitchboardState))
+    );
+    assert_eq!(
+        trigger_with(|env| (env.escrow, other_wsol_account(env))),
+        Err(l2_error(RandomnessRequestError::InvalidEscrow))
+    );
+}
+
+#[test]
+fn test_trigger_update_insufficient_escrow() {
+    let mut env = Env::new_initialized();
+    ready_for_update(&mut env);
+    switchboard::set_run_costs(
+        &mut env.test,
+        &env.attestation_queue,
+        1_000,
+        &env.function,
+        0,
+    );
+    let ix = env.set_bounty(&env.payer, 4_000);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    env.fund_escrow(4_999);
+
+    let ix = env.trigger_update(randomness(0, 0));
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer, env.enclave_signer]),
+        Err(l2_error(RandomnessRequestError::InsufficientEscrow))
+    );
+
+    env.fund_escrow(1);
+    let ix = env.trigger_update(randomness(0, 0));
+    env.test
+        .process(&[ix], &[env.payer, env.enclave_signer])
+        .unwrap();
+    let request = switchboard::request(&env.test, &env.request);
+    assert_eq!(request.active_request.bounty, 4_000);
+}
+
+// RETRIGGER_UPDATE
+
+#[test]
+fn test_retrigger_update() {
+    let mut env = Env::new_initialized();
+    warp_past_late_window(&mut env);
+    let keeper = Pubkey::new_unique();
+    env.test.airdrop(&keeper, 1_000_000_000);
+
+    let ix = env.retrigger_update(&keeper);
+    env.test.process(&[ix], &[keeper]).unwrap();
+
+    let now = env.test.clock.unix_timestamp;
+    assert_eq!(env.mint_manager().next_update_ts, now);
+    let request = switchboard::request(&env.test, &env.request);
+    assert_eq!(request.active_request.request_slot, env.test.clock.slot);
+    assert_eq!(request.active_request.valid_after_slot, 0);
+    assert_eq!(env.mint_manager().valid_after_slot, env.test.clock.slot);
+
+    let events = env.test.events::<RequestRetriggered>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].payer, keeper);
+    assert_eq!(events[0].timestamp, now);
+
+    // The late window restarts
+    let ix = env.retrigger_update(&keeper);
+    assert_eq!(
+        env.test.process(&[ix], &[keeper]),
+        Err(l2_error(RandomnessRequestError::RequestNotStale))
+    );
+}
+
+#[test]
+fn test_retrigger_update_insufficient_escrow() {
+    let mut env = Env::new_initialized();
+    warp_past_late_window(&mut env);
+    switchboard::set_run_costs(
+        &mut env.test,
+        &env.attestation_queue,
+        0,
+        &env.function,
+        2_000,
+    );
+
+    let ix = env.retrigger_update(&env.payer);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(RandomnessRequestError::InsufficientEscrow))
+    );
+    env.fund_escrow(2_000);
+    let ix = env.retrigger_update(&env.payer);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+}
+
+#[test]
+fn test_retrigger_update_not_stale() {
+    let mut env = Env::new_initialized();
+    env.test.warp(2 * SECONDS_PER_HOUR);
+
+    let ix = env.retrigger_update(&env.payer);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(RandomnessRequestError::RequestNotStale))
+    );
+
+    // Past the late window by the clock, but 400ms slots have not reached valid_after_slot
+    // plus the window yet, the oracle may still be about to run
+    env.test.warp(1);
+    let ix = env.retrigger_update(&env.payer);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(RandomnessRequestError::RequestNotStale))
+    );
+
+    warp_past_late_window(&mut env);
+    let ix = env.retrigger_update(&env.payer);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+}
+
+#[test]
+fn test_retrigger_update_incorrect_request() {
+    let mut env = Env::new_initialized();
+    warp_past_late_window(&mut env);
+    let copy = Pubkey::new_unique();
+    let account = env.test.account(&env.request).unwrap().clone();
+    env.test.set_account(copy, account);
+
+    let mut ix = env.retrigger_update(&env.payer);
+    swap_account(&mut ix, &env.request, &copy);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(
+            RandomnessRequestError::IncorrectSwitchboardRequest
+        ))
+    );
+}
+
+#[test]
+fn test_retrigger_update_incorrect_function() {
+    let mut env = Env::new_initialized();
+    warp_past_late_window(&mut env);
+    env.function = switchboard::add_function(&mut env.test, &env.attestation_queue);
+
+    let ix = env.retrigger_update(&env.payer);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(
+            RandomnessRequestError::IncorrectSwitchboardFunction
+        ))
+    );
+}
+
+// ESCROW
+
+/// A wSOL account that is not the request escrow.
+fn other_wsol_account(env: &mut Env) -> Pubkey {
+    let owner = Pubkey::new_unique();
+    let ix = create_associated_token_account(
+        &env.payer,
+        &owner,
+        &spl_token::native_mint::ID,
+        &spl_token::ID,
+    );
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    get_associated_token_address(&owner, &spl_token::native_mint::ID)
+}
+
+#[test]
+fn test_deposit_escrow() {
+    let mut env = Env::new_initialized();
+    let depositor = Pubkey::new_unique();
+    env.test.airdrop(&depositor, 1_000_000_000);
+
+    for _ in 0..2 {
+        let ix = env.deposit_escrow(&depositor, 250_000_000);
+        env.test.process(&[ix], &[depositor]).unwrap();
+    }
+    assert_eq!(env.balance(&env.escrow), 500_000_000);
+    assert_eq!(env.test.lamports(&depositor), 500_000_000);
+
+    let events = env.test.events::<EscrowDeposited>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].mint, env.mint);
+    assert_eq!(events[0].depositor, depositor);
+    assert_eq!(events[0].amount, 250_000_000);
+    assert_eq!(events[0].balance, 500_000_000);
+
+    let other = other_wsol_account(&mut env);
+    let mut ix = env.deposit_escrow(&depositor, 1);
+    swap_account(&mut ix, &env.escrow, &other);
+    assert_eq!(
+        env.test.process(&[ix], &[depositor]),
+        Err(l2_error(RandomnessRequestError::InvalidEscrow))
+    );
+}
+
+#[test]
+fn test_escrow_runway() {
+    let mut env = Env::new_initialized();
+    let runway = |env: &mut Env| {
+        let ix = env.escrow_runway();
+        env.test.process(&[ix], &[]).unwrap();
+        env.test.return_data::<EscrowRunway>().unwrap()
+    };
+    // Nothing to pay for yet
+    assert_eq!(runway(&mut env), EscrowRunway::new(0, 0));
+    assert_eq!(runway(&mut env).runs, u64::MAX);
+
+    switchboard::set_run_costs(
+        &mut env.test,
+        &env.attestation_queue,
+        1_000,
+        &env.function,
+        2_000,
+    );
+    let ix = env.set_bounty(&env.payer, 3_000);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    let events = env.test.events::<BountyUpdated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].bounty, 3_000);
+    assert_eq!(env.mint_manager().bounty, 3_000);
+
+    env.fund_escrow(65_000);
+    assert_eq!(
+        runway(&mut env),
+        EscrowRunway {
+            balance: 65_000,
+            cost_per_run: 6_000,
+            runs: 10,
+        }
+    );
+
+    let stranger = Pubkey::new_unique();
+    let ix = env.set_bounty(&stranger, 0);
+    assert_eq!(
+        env.test.process(&[ix], &[stranger]),
+        Err(l2_error(RandomnessRequestError::InvalidAuthority))
+    );
+}
+
+// SET_SWITCHBOARD_FUNCTION
+
+#[test]
+fn test_set_switchboard_function() {
+    let mut env = Env::new_initialized();
+    env.fund_escrow(1_000_000_000);
+    env.test.warp(600);
+    let (old_request, old_escrow) = (env.request, env.escrow);
+    let queue = switchboard::add_queue(&mut env.test);
+    let function = switchboard::add_function(&mut env.test, &queue);
+    let request = Pubkey::new_unique();
+    let authority = env.payer;
+
+    let ix = env.set_switchboard_function(&authority, &queue, &function, &request);
+    env.test.process(&[ix], &[authority, request]).unwrap();
+
+    assert!(env.test.account(&old_request).is_none());
+    assert!(env.test.account(&old_escrow).is_none());
+    let wsol = get_associated_token_address(&env.mint_manager, &spl_token::native_mint::ID);
+    assert!(env.test.account(&wsol).is_none());
+
+    let escrow = get_associated_token_address(&request, &spl_token::native_mint::ID);
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.switchboard_function, function);
+    assert_eq!(mint_manager.attestation_queue, queue);
+    assert_eq!(mint_manager.switchboard_request, Some(request));
+    assert_eq!(mint_manager.switchboard_request_escrow, escrow);
+
+    // Escrow balance carried over, new request triggered for the update already scheduled
+    let escrow_data =
+        spl_token::state::Account::unpack(&env.test.account(&escrow).unwrap().data).unwrap();
+    assert_eq!(escrow_data.amount, 1_000_000_000);
+    let request_data = switchboard::request(&env.test, &request);
+    assert_eq!(request_data.authority, env.mint_manager);
+    assert_eq!(request_data.function, function);
+    assert_eq!(request_data.attestation_queue, queue);
+    assert_eq!(request_data.is_triggered, 1);
+    assert_eq!(
+        request_data.active_request.valid_after_slot,
+        env.test.clock.slot + (SECONDS_PER_HOUR as u64 - 600) * 1000 / MIN_SLOT_DURATION_MS
+    );
+    let params = container_params::ContainerParams::decode(&request_data.container_params).unwrap();
+    assert_eq!(params.escrow, escrow);
+
+    let events = env.test.events::<SwitchboardFunctionUpdated>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].old_function, env.function);
+    assert_eq!(events[0].function, function);
+    assert_eq!(events[0].request, request);
+    assert_eq!(events[0].escrow_amount, 1_000_000_000);
+
+    // The next update goes through the new request
+    env.function = function;
+    env.attestation_queue = queue;
+    env.request = request;
+    env.escrow = escrow;
+    ready_for_update(&mut env);
+    let ix = env.trigger_update(randomness(0, 0));
+    env.test
+        .process(&[ix], &[env.payer, env.enclave_signer])
+        .unwrap();
+}
+
+#[test]
+fn test_set_switchboard_function_invalid_authority() {
+    let mut env = Env::new_initialized();
+    let stranger = Pubkey::new_unique();
+    env.test.airdrop(&stranger, 1_000_000_000);
+    let request = Pubkey::new_unique();
+
+    let ix =
+        env.set_switchboard_function(&stranger, &env.attestation_queue, &env.function, &request);
+    assert_eq!(
+        env.test.process(&[ix], &[stranger, request]),
+        Err(l2_error(RandomnessRequestError::InvalidAuthority))
+    );
+}
+
+// FEE OVERRIDE AND PAUSE
+
+#[test]
+fn test_set_fee_override() {
+    let mut env = Env::new_initialized();
+    let slot = env.test.clock.slot;
+
+    let ix = env.set_fee_override(&env.payer, 250, 100_000);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.current_transfer_fee_bp, 250);
+    assert_eq!(mint_manager.fee_override_until_slot, slot + 100_000);
+    let config = env.transfer_fee_config();
+    assert_eq!(
+        u16::from(config.newer_transfer_fee.transfer_fee_basis_points),
+        250
+    );
+    let events = env.test.events::<FeeOverrideSet>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].old_bp, INITIAL_FEE_BP);
+    assert_eq!(events[0].new_bp, 250);
+    assert_eq!(
+        events[0].effective_epoch,
+        u64::from(config.newer_transfer_fee.epoch)
+    );
+    assert_eq!(events[0].effective_epoch, env.test.clock.epoch + 2);
+    assert_eq!(events[0].until_slot, slot + 100_000);
+
+    // Still rescheduled, the fee stays pinned
+    ready_for_update(&mut env);
+    let ix = env.trigger_update(randomness(3, 0));
+    env.test
+        .process(&[ix], &[env.payer, env.enclave_signer])
+        .unwrap();
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.current_transfer_fee_bp, 250);
+    assert_eq!(
+        mint_manager.next_update_ts,
+        env.test.clock.unix_timestamp + mint_manager.min_interval_secs
+    );
+    let request = switchboard::request(&env.test, &env.request);
+    assert_eq!(request.status, RequestStatus::RequestPending);
+    let events = env.test.events::<FeeUpdateSkipped>();
+    assert_eq!(events.len(), 1);
+    assert!(!events[0].paused);
+    assert_eq!(events[0].fee_override_until_slot, slot + 100_000);
+    assert!(env.test.events::<FeeUpdated>().is_empty());
+
+    // Randomized again once the override ran out
+    ready_for_update(&mut env);
+    assert!(env.test.clock.slot >= slot + 100_000);
+    let ix = env.trigger_update(randomness(3, 0));
+    env.test
+        .process(&[ix], &[env.payer, env.enclave_signer])
+        .unwrap();
+    assert_eq!(env.mint_manager().current_transfer_fee_bp, 3_000);
+}
+
+#[test]
+fn test_set_fee_override_validation() {
+    let mut env = Env::new_initialized();
+
+    let ix = env.set_fee_override(&env.payer, 10_001, 1);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(RandomnessRequestError::InvalidFeeOverride))
+    );
+    let stranger = Pubkey::new_unique();
+    let ix = env.set_fee_override(&stranger, 0, 1);
+    assert_eq!(
+        env.test.process(&[ix], &[stranger]),
+        Err(l2_error(RandomnessRequestError::InvalidAuthority))
+    );
+}
+
+#[test]
+fn test_pause() {
+    let mut env = Env::new_initialized();
+
+    let ix = env.set_paused(&env.payer, true);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    assert!(env.mint_manager().paused);
+    let events = env.test.events::<PauseUpdated>();
+    assert_eq!(events.len(), 1);
+    assert!(events[0].paused);
+
+    ready_for_update(&mut env);
+    let ix = env.trigger_update(randomness(3, 0));
+    env.test
+        .process(&[ix], &[env.payer, env.enclave_signer])
+        .unwrap();
+    assert_eq!(env.mint_manager().current_transfer_fee_bp, INITIAL_FEE_BP);
+    let config = env.transfer_fee_config();
+    assert_eq!(
+        u16::from(config.newer_transfer_fee.transfer_fee_basis_points),
+        INITIAL_FEE_BP
+    );
+    let events = env.test.events::<FeeUpdateSkipped>();
+    assert_eq!(events.len(), 1);
+    assert!(events[0].paused);
+
+    let ix = env.set_paused(&env.payer, false);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    ready_for_update(&mut env);
+    let ix = env.trigger_update(randomness(3, 0));
+    env.test
+        .process(&[ix], &[env.payer, env.enclave_signer])
+        .unwrap();
+    assert_eq!(env.mint_manager().current_transfer_fee_bp, 3_000);
+
+    let stranger = Pubkey::new_unique();
+    let ix = env.set_paused(&stranger, true);
+    assert_eq!(
+        env.test.process(&[ix], &[stranger]),
+        Err(l2_error(RandomnessRequestError::InvalidAuthority))
+    );
+}
+
+// FEE GUARDRAILS
+
+#[test]
+fn test_fee_guardrails() {
+    let mut env = Env::new_initialized();
+
+    let ix = env.set_fee_guardrails(&env.payer, Some(500), Some(300));
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.fee_ceiling_bp, Some(500));
+    assert_eq!(mint_manager.max_fee_delta_bp, Some(300));
+    let events = env.test.events::<FeeGuardrailsSet>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].fee_ceiling_bp, Some(500));
+    assert_eq!(events[0].max_fee_delta_bp, Some(300));
+
+    let mut trigger = |fee_random: u64| {
+        ready_for_update(&mut env);
+        let ix = env.trigger_update(randomness(fee_random, 0));
+        env.test
+            .process(&[ix], &[env.payer, env.enclave_signer])
+            .unwrap();
+        env.mint_manager().current_transfer_fee_bp
+    };
+    // 30% from the mint's 1% only moves up by the max change
+    assert_eq!(trigger(3), INITIAL_FEE_BP + 300);
+    // then stops at the 5% ceiling
+    assert_eq!(trigger(3), 500);
+    // 0% only moves down by the max change
+    assert_eq!(trigger(0), 200);
+    let config = env.transfer_fee_config();
+    assert_eq!(
+        u16::from(config.newer_transfer_fee.transfer_fee_basis_points),
+        200
+    );
+    assert_eq!(env.test.events::<FeeUpdated>()[0].new_bp, 200);
+
+    // Immutable once set
+    let ix = env.set_fee_guardrails(&env.payer, Some(3_000), None);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(RandomnessRequestError::FeeGuardrailAlreadySet))
+    );
+    let ix = env.set_fee_guardrails(&env.payer, None, Some(1_000));
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(RandomnessRequestError::FeeGuardrailAlreadySet))
+    );
+}
+
+#[test]
+fn test_fee_guardrails_override() {
+    let mut env = Env::new_initialized();
+    let ix = env.set_fee_guardrails(&env.payer, Some(2_000), Some(500));
+    env.test.process(&[ix], &[env.payer]).unwrap();
+
+    let ix = env.set_fee_override(&env.payer, 2_100, 1);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(RandomnessRequestError::FeeAboveCeiling))
+    );
+    // Measured from the fee the mint started with
+    let ix = env.set_fee_override(&env.payer, INITIAL_FEE_BP + 501, 1);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(RandomnessRequestError::FeeChangeTooLarge))
+    );
+    let ix = env.set_fee_override(&env.payer, INITIAL_FEE_BP + 500, 1);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    assert_eq!(
+        env.mint_manager().current_transfer_fee_bp,
+        INITIAL_FEE_BP + 500
+    );
+}
+
+#[test]
+fn test_fee_guardrails_validation() {
+    let mut env = Env::new_initialized();
+
+    let ix = env.set_fee_guardrails(&env.payer, Some(10_001), None);
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(RandomnessRequestError::InvalidFeeGuardrails))
+    );
+    let ix = env.set_fee_guardrails(&env.payer, None, Some(0));
+    assert_eq!(
+        env.test.process(&[ix], &[env.payer]),
+        Err(l2_error(RandomnessRequestError::InvalidFeeGuardrails))
+    );
+    let stranger = Pubkey::new_unique();
+    let ix = env.set_fee_guardrails(&stranger, Some(1_000), None);
+    assert_eq!(
+        env.test.process(&[ix], &[stranger]),
+        Err(l2_error(RandomnessRequestError::InvalidAuthority))
+    );
+
+    // Either bound can be set on its own
+    let ix = env.set_fee_guardrails(&env.payer, Some(1_000), None);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    let ix = env.set_fee_guardrails(&env.payer, None, Some(100));
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.fee_ceiling_bp, Some(1_000));
+    assert_eq!(mint_manager.max_fee_delta_bp, Some(100));
+}
+
+// COLLECT AND BURN
+
+#[test]
+fn test_collect_and_burn_from_account() {
+    let mut env = Env::new_initialized();
+    let (_, to) = withheld_transfer(&mut env, 1_000_000);
+    let source = token_2022_ata(&to, &env.mint);
+    assert_eq!(env.withheld(&source), 10_000);
+    let supply = env.supply();
+
+    let ix = env.collect_and_burn_from_account(&[source], None, None);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+
+    assert_eq!(env.withheld(&source), 0);
+    assert_eq!(env.supply(), supply - 10_000);
+    assert_eq!(
+        env.balance(&token_2022_ata(&env.mint_manager, &env.mint)),
+        0
+    );
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.total_collected, 10_000);
+    assert_eq!(mint_manager.total_burned, 10_000);
+    assert_eq!(mint_manager.collection_count, 1);
+    assert_eq!(mint_manager.last_collection_slot, env.test.clock.slot);
+
+    let collected = env.test.events::<FeesCollected>();
+    assert_eq!(collected.len(), 1);
+    assert_eq!(collected[0].amount, 10_000);
+    assert_eq!(collected[0].to_treasury, 0);
+    let burned = env.test.events::<TokensBurned>();
+    assert_eq!(burned.len(), 1);
+    assert_eq!(burned[0].amount, 10_000);
+}
+
+#[test]
+fn test_harvest_and_collect_and_burn_from_mint() {
+    let mut env = Env::new_initialized();
+    let (_, to) = withheld_transfer(&mut env, 1_000_000);
+    let source = token_2022_ata(&to, &env.mint);
+
+    let ix = env.harvest_withheld_tokens_to_mint(&[source]);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    assert_eq!(env.withheld(&source), 0);
+    assert_eq!(u64::from(env.transfer_fee_config().withheld_amount), 10_000);
+    assert_eq!(env.test.events::<WithheldHarvested>()[0].source_count, 1);
+
+    let supply = env.supply();
+    let ix = env.collect_and_burn_from_mint(None, None);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+
+    assert_eq!(u64::from(env.transfer_fee_config().withheld_amount), 0);
+    assert_eq!(env.supply(), supply - 10_000);
+    assert_eq!(env.mint_manager().total_burned, 10_000);
+    assert_eq!(env.test.events::<TokensBurned>()[0].amount, 10_000);
+}
+
+#[test]
+fn test_collect_split() {
+    let mut env = Env::new_initialized();
+    let treasury = env.create_ata(&Pubkey::new_unique());
+    let rewards_vault = env.create_ata(&Pubkey::new_unique());
+    let authority = env.payer;
+    let split = FeeDestination::Split {
+        burn_bp: 5_000,
+        treasury_bp: 3_000,
+        rewards_bp: 2_000,
+    };
+    let ix = env.set_fee_destination(&authority, split, Some(treasury), Some(rewards_vault));
+    env.test.process(&[ix], &[authority]).unwrap();
+
+    let (_, to) = withheld_transfer(&mut env, 1_000_000);
+    let source = token_2022_ata(&to, &env.mint);
+    let supply = env.supply();
+    let ix = env.collect_and_burn_from_account(&[source], Some(treasury), Some(rewards_vault));
+    env.test.process(&[ix], &[env.payer]).unwrap();
+
+    // Forwarded amounts pay the transfer fee again, withheld on the destination
+    assert_eq!(env.balance(&treasury), 2_970);
+    assert_eq!(env.withheld(&treasury), 30);
+    assert_eq!(env.balance(&rewards_vault), 1_980);
+    assert_eq!(env.withheld(&rewards_vault), 20);
+    assert_eq!(env.supply(), supply - 5_000);
+
+    // Everything withdrawn is collected, the destinations count what arrived
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.total_burned, 5_000);
+    assert_eq!(mint_manager.total_to_treasury, 2_970);
+    assert_eq!(mint_manager.total_to_rewards, 1_980);
+    assert_eq!(mint_manager.total_collected, 10_000);
+
+    let collected = env.test.events::<FeesCollected>();
+    assert_eq!(collected[0].amount, 10_000);
+    assert_eq!(collected[0].to_treasury, 2_970);
+    assert_eq!(collected[0].to_rewards, 1_980);
+}
+
+#[test]
+fn test_collect_to_treasury() {
+    let mut env = Env::new_initialized();
+    let treasury = env.create_ata(&Pubkey::new_unique());
+    let authority = env.payer;
+    let ix = env.set_fee_destination(&authority, FeeDestination::Treasury, Some(treasury), None);
+    env.test.process(&[ix], &[authority]).unwrap();
+
+    let (_, to) = withheld_transfer(&mut env, 1_000_000);
+    let source = token_2022_ata(&to, &env.mint);
+    let ix = env.collect_and_burn_from_account(&[source], Some(treasury), None);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+
+    // Withdrawn straight into the treasury, no second fee
+    assert_eq!(env.balance(&treasury), 10_000);
+    assert_eq!(env.withheld(&treasury), 0);
+    assert_eq!(
+        env.balance(&token_2022_ata(&env.mint_manager, &env.mint)),
+        0
+    );
+    let mint_manager = env.mint_manager();
+    assert_eq!(mint_manager.total_to_treasury, 10_000);
+    assert_eq!(mint_manager.total_collected, 10_000);
+    assert_eq!(mint_manager.total_burned, 0);
+    assert!(env.test.events::<TokensBurned>().is_empty());
+
+    // Same from the mint
+    let (_, to) = withheld_transfer(&mut env, 1_000_000);
+    let ix = env.harvest_withheld_tokens_to_mint(&[token_2022_ata(&to, &env.mint)]);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    let ix = env.collect_and_burn_from_mint(Some(treasury), None);
+    env.test.process(&[ix], &[env.payer]).unwrap();
+    assert_eq!(env.balance(&treasury), 20_000);
+    assert_eq!(env.mint_manager().total_to_treasury, 20_000);
+    assert_eq!(env.test.events::<FeesCollected>()[0].to_treasury, 10_000);
+}
+
+// AUTHORITY GATED SETTINGS
+
+#[test]
+fn test_authority_transfer() {
+    let mut env = Env::new_initialized();
+    let authority = env.payer;
+    let new_authority = Pubkey::new_unique();
+    let stranger = Pubkey::new_unique();
+
+    let ix = env.propose_authority(&authority, &new_authority);
+    env.test.process(&[ix], &[authority]).unwrap();
+    assert_eq!(env.mint_manager().pending_authority, Some(new_authority));
+
+    let ix = env.accept_authority(&stranger);
+    assert_eq!(
+        env.test.process(&[ix], &[stranger]),
+        Err(l2_error(RandomnessRequestError::InvalidPendingAuthority))
+    );
+
+    let ix = env.accept_authority(&new_authority);
+    env.test.process(&[ix], &[new_authority]).unwrap();
+    assert_eq!(env.mint_manager().authority, Some(new_authority));
+    assert_eq!(env.mint_manager().pending_authority, None);
+    assert_eq!(
+        env.test.events::<AuthorityAccepted>()[0].authority,
+        new_authority
+    );
+
+    let ix = env.set_late_window(&authority, 60);
+    assert_eq!(
+        env.test.process(&[ix], &[authority]),
+        Err(l2_error(RandomnessRequestError::InvalidAuthority))
+    );
+
+    let ix = env.renounce_authority(&new_authority);
+    env.test.process(&[ix], &[new_authority]).unwrap();
+    assert_eq!(env.mint_manager().authority, None);
+    let ix = env.set_late_window(&new_authority, 60);
+    assert_eq!(
+        env.test.process(&[ix], &[new_authority]),
+        Err(l2_error(RandomnessRequestError::InvalidAuthority))
+    );
+}
+
+#[test]
+fn test_settings_validation() {
+    let mut env = Env::new_initialized();
+    let authority = env.payer;
+
+    let cases = [
+        (
+            env.set_fee_tiers(&authority, vec![]),
+            RandomnessRequestError::InvalidFeeTiers,
+        ),
+        (
+            env.set_fee_tiers(
+                &authority,
+                vec![FeeTier {
+                    bp: 10_001,
+                    weight: 1,
+                }],
+            ),
+            RandomnessRequestError::InvalidFeeTiers,
+        ),
+        (
+            env.set_fee_tiers(&authority, vec![FeeTier { bp: 100, weight: 0 }]),
+            RandomnessRequestError::InvalidFeeTiers,
+        ),
+        (
+            env.set_max_fee(&authority, MaxFee::WholeTokens(u64::MAX)),
+            RandomnessRequestError::MaxFeeOverflow,
+        ),
+        (
+            env.set_fee_destination(
+                &authority,
+                FeeDestination::Split {
+                    burn_bp: 1,
+                    treasury_bp: 1,
+                    rewards_bp: 1,
+                },
+                None,
+                None,
+            ),
+            RandomnessRequestError::InvalidFeeSplit,
+        ),
+        (
+            env.set_fee_destination(&authority, FeeDestination::Treasury, None, None),
+            RandomnessRequestError::MissingTreasury,
+        ),
+        (
+            env.set_fee_destination(
+                &authority,
+                FeeDestination::Split {
+                    burn_bp: 5_000,
+                    treasury_bp: 0,
+                    rewards_bp: 5_000,
+                },
+                None,
+                None,
+            ),
+            RandomnessRequestError::MissingRewardsVault,
+        ),
+        (
+            env.set_interval_bounds(&authority, 0, 60),
+            RandomnessRequestError::InvalidIntervalBounds,
+        ),
+        (
+            env.set_interval_bounds(&authority, 120, 60),
+            RandomnessRequestError::InvalidIntervalBounds,
+        ),
+        (
+            env.set_interval_bounds(&authority, 1, MAX_INTERVAL_SECS + 1),
+            RandomnessRequestError::InvalidIntervalBounds,
+        ),
+        (
+            env.set_interval_bounds(&authority, 60, 151_199),
+            RandomnessRequestError::IntervalBelowEpoch,
+        ),
+        (
+            env.set_late_window(&authority, -1),
+            RandomnessRequestError::InvalidLateWindow,
+        ),
+    ];
+    for (ix, error) in cases {
+        assert_eq!(env.test.process(&[ix], &[authority]), Err(l2_error(error)));
+    }
+}
+
+// ArrayOverflow, StaleData, InvalidTrustedSigner, InvalidMrEnclave, InvalidSymbol,
+// InvalidSwitchboardFunction, FunctionValidationFailed, SwitchboardRequestNotSuccessful,
+// RoundInactive, HouseInsufficientFunds and RandomResultOutOfBounds have no test: the program
+// never returns them. They come from the Switchboard example the program started from, removing
+// them would shift the code of every error declared after them.
@@ -1,5 +1,5 @@
 import * as anchor from "@coral-xyz/anchor";
-import { getGlobalPda } from "./pdas";
+import { getMintManagerPda } from "./pdas";
 import { setupTestEnv } from "./script";
 import {
   AttestationQueueAccount,
@@ -42,9 +42,7 @@ describe("l2", () => {
   const user2 = anchor.web3.Keypair.generate();
   let mint: anchor.web3.PublicKey;
 
-  const globalPda = getGlobalPda(program);
   const pubkeys = {
-    globalPda,
     globalOwner,
     user1,
     user2,
@@ -91,19 +89,23 @@ describe("l2", () => {
 
   it("Wait and check for trigger!", async () => {
     const slotBefore = await program.provider.connection.getSlot();
-    const globalPda = getGlobalPda(program);
-    const globalDataPre = await program.account.global.fetch(globalPda);
+    const mintManagerPda = getMintManagerPda(program, mint);
+    const globalDataPre = await program.account.mintManager.fetch(
+      mintManagerPda
+    );
     console.log("WAIT", slotBefore);
     console.log("hourToNextUpdate", globalDataPre.currentTransferFeeBp);
-    console.log("hourToNextUpdate", globalDataPre.hourToNextUpdate);
-    console.log("UPDATE IN", +globalDataPre.nextUpdateSlot);
+    console.log("updateIntervalSecs", +globalDataPre.updateIntervalSecs);
+    console.log("UPDATE IN", +globalDataPre.nextUpdateTs);
     await delay(10_000);
     const slotAfter = await program.provider.connection.getSlot();
     console.log("CLEAR", slotAfter);
-    const globalData = await program.account.global.fetch(globalPda);
+    const globalData = await program.account.mintManager.fetch(
+      mintManagerPda
+    );
     console.log("globalData", globalData.currentTransferFeeBp);
-    console.log("globalData", globalData.hourToNextUpdate);
-    console.log("globalData", +globalData.nextUpdateSlot);
+    console.log("globalData", +globalData.updateIntervalSecs);
+    console.log("globalData", +globalData.nextUpdateTs);
 
     // // First, generate a new keypair to sign our instruction
     // // Normally this happens within the enclave
@@ -9,7 +9,6 @@ import {
 } from "@switchboard-xyz/solana.js";
 
 export interface IPubkeys {
-  globalPda: anchor.web3.PublicKey;
   globalOwner: anchor.web3.Keypair;
   user1: anchor.web3.Keypair;
 }
@@ -19,10 +18,11 @@ export interface ISwitchboardObj {
   switchboardRequestKeypair: anchor.web3.Keypair;
 }
 
-export const getGlobalPda = (program: Program<L2>) => {
-  const [globalPda, _globalPdaBump] = PublicKey.findProgramAddressSync(
-    [Buffer.from("global")],
-    program.programId
-  );
-  return globalPda;
+export const getMintManagerPda = (program: Program<L2>, mint: PublicKey) => {
+  const [mintManagerPda, _mintManagerPdaBump] =
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_manager"), mint.toBuffer()],
+      program.programId
+    );
+  return mintManagerPda;
 };
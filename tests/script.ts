@@ -7,9 +7,11 @@ import {
   TOKEN_PROGRAM_ID,
   createAssociatedTokenAccount,
   createMint,
+  getMint,
+  getTransferFeeConfig,
   mintTo,
 } from "@solana/spl-token";
-import { IPubkeys, ISwitchboardObj, getGlobalPda } from "./pdas";
+import { IPubkeys, ISwitchboardObj, getMintManagerPda } from "./pdas";
 import { assert } from "chai";
 import { L2 } from "../target/types/l2";
 
@@ -19,7 +21,7 @@ export const setupTestEnv = async (
   pubkeys: IPubkeys,
   sbObj: ISwitchboardObj
 ) => {
-  // Create global
+  // Create mint manager
   const HOUR_TO_NEXT_UPDATE = 48;
   const switchboardEscrow = anchor.utils.token.associatedAddress({
     mint: sbObj.switchboard.program.mint.address,
@@ -30,8 +32,8 @@ export const setupTestEnv = async (
     await program.methods
       .initGlobal(HOUR_TO_NEXT_UPDATE)
       .accounts({
-        global: pubkeys.globalPda,
         mint: mint, // ADD TOKEN22 MINT
+        mintManager: getMintManagerPda(program, mint),
         payer: pubkeys.globalOwner.publicKey,
         switchboard: sbObj.switchboard.program.attestationProgramId,
         switchboardState:
@@ -50,12 +52,45 @@ export const setupTestEnv = async (
   } catch (error) {
     console.log("ERROR", error);
   }
-  const globalPda = getGlobalPda(program);
-  const globalData = await program.account.global.fetch(globalPda);
+  const mintManagerPda = getMintManagerPda(program, mint);
+  const globalData = await program.account.mintManager.fetch(mintManagerPda);
 
-  assert.equal(+globalData.hourToNextUpdate, HOUR_TO_NEXT_UPDATE);
-  assert.equal(+globalData.currentTransferFeeBp, 100_00);
+  assert.equal(+globalData.updateIntervalSecs, HOUR_TO_NEXT_UPDATE * 3600);
+  // One epoch at 350ms slots up to the 85h cap
+  const { slotsPerEpoch } =
+    await program.provider.connection.getEpochSchedule();
+  assert.equal(
+    +globalData.minIntervalSecs,
+    Math.floor((slotsPerEpoch * 350) / 1000)
+  );
+  assert.equal(+globalData.maxIntervalSecs, 85 * 3600);
+  // Seeded from the fee already set on the mint
+  const transferFeeConfig = getTransferFeeConfig(
+    await getMint(
+      program.provider.connection,
+      mint,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    )
+  );
+  assert.equal(
+    +globalData.currentTransferFeeBp,
+    transferFeeConfig.newerTransferFee.transferFeeBasisPoints
+  );
+  assert.equal(
+    globalData.maxFee.toString(),
+    transferFeeConfig.newerTransferFee.maximumFee.toString()
+  );
+  assert.deepEqual(
+    globalData.feeTiers.map((tier) => [tier.bp, tier.weight]),
+    [0, 10_00, 20_00, 30_00, 40_00, 50_00, 60_00].map((bp) => [bp, 1])
+  );
   assert.equal(globalData.mint.toString(), mint.toString(), "123");
+  assert.equal(
+    globalData.authority.toString(),
+    pubkeys.globalOwner.publicKey.toString()
+  );
+  assert.equal(globalData.pendingAuthority, null);
 
   assert.equal(
     globalData.switchboardFunction.toString(),
@@ -71,5 +106,12 @@ export const setupTestEnv = async (
     sbObj.switchboard.publicKey.toString(),
     "888"
   );
-  assert.equal(globalData.switchboardRequest, null);
+  assert.equal(
+    globalData.switchboardRequest.toString(),
+    sbObj.switchboardRequestKeypair.publicKey.toString()
+  );
+  assert.equal(
+    globalData.switchboardRequestEscrow.toString(),
+    switchboardEscrow.toString()
+  );
 };
@@ -22,7 +22,7 @@ import {
   FunctionAccount,
   SwitchboardProgram,
 } from "@switchboard-xyz/solana.js";
-import { IPubkeys } from "./pdas";
+import { IPubkeys, getMintManagerPda } from "./pdas";
 import { L2 } from "../target/types/l2";
 
 export const createTransferFeeMint = async (
@@ -33,9 +33,9 @@ export const createTransferFeeMint = async (
   maxFee: bigint,
   mintAuthority: PublicKey,
   transferFeeConfigAuthority: PublicKey,
-  withdrawWithheldAuthority: PublicKey
+  withdrawWithheldAuthority: PublicKey,
+  mintKeypair: Keypair = Keypair.generate()
 ): Promise<PublicKey> => {
-  const mintKeypair = Keypair.generate();
   const mint = mintKeypair.publicKey;
 
   const extensions = [ExtensionType.TransferFeeConfig];
@@ -155,7 +155,7 @@ export const envSetup = async (program: Program<L2>, pubkeys: IPubkeys) => {
     // );
 
     console.log("TEST 2");
-    const arr = Object.values(pubkeys).slice(1);
+    const arr = Object.values(pubkeys);
 
     for (let i = 1; i < arr.length; i++) {
       let ix = anchor.web3.SystemProgram.transfer({
@@ -170,17 +170,20 @@ export const envSetup = async (program: Program<L2>, pubkeys: IPubkeys) => {
     await signAndSendTx(program.provider.connection, tx, pubkeys.globalOwner);
 
     console.log("TEST 4");
-    // TODO: CREATE TOKEN22 MINT, set GLOBAL to AUTHORITY
+    // CREATE TOKEN22 MINT, set MINT MANAGER to AUTHORITY
     const TRANSFER_FEE = 100_00; // 100%
+    const mintKeypair = Keypair.generate();
+    const mintManagerPda = getMintManagerPda(program, mintKeypair.publicKey);
     const mint = await createTransferFeeMint(
       program.provider.connection,
       pubkeys.user1,
       TRANSFER_FEE,
       9,
       BigInt(0),
-      pubkeys.user1.publicKey, // MINT AUTHORITY
-      pubkeys.globalPda,
-      pubkeys.globalPda
+      pubkeys.globalOwner.publicKey, // MINT AUTHORITY, HAS TO PAY FOR init_global
+      mintManagerPda,
+      mintManagerPda,
+      mintKeypair
     );
 
     console.log("TEST 5");
@@ -202,7 +205,7 @@ export const envSetup = async (program: Program<L2>, pubkeys: IPubkeys) => {
         pubkeys.globalOwner,
         mint,
         ata,
-        pubkeys.user1,
+        pubkeys.globalOwner,
         10_000_00,
         undefined,
         undefined,
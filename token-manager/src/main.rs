@@ -1,8 +1,6 @@
-use std::str::FromStr;
-
-pub use switchboard_solana::{get_ixn_discriminator, switchboard_function, sb_error};
+pub use switchboard_solana::{switchboard_function, sb_error};
 pub use switchboard_solana::prelude::*;
-use crate::solana_sdk::commitment_config::CommitmentConfig;
+use l2_client::{SwitchboardAccounts, TriggerUpdateParams};
 
 mod params;
 pub use params::*;
@@ -11,113 +9,150 @@ pub use params::*;
 pub async fn sb_function(runner: FunctionRunner, params: Vec<u8>) -> Result<Vec<Instruction>, SbFunctionError> {
     // parse and validate user provided request params
     println!("incoming param is: {:?}", params);
-    let data: ContainerParams = ContainerParams::decode(&params).map_err(|_| Error::ArgParseFail)?;
-    // Generate our random result
-    println!("max value is: {}", data.max_value);
-    let random_result = generate_randomness(1, data.max_value);
-    println!("random number is: {}", random_result);
-    let mut random_bytes = random_result.to_le_bytes().to_vec();
-
-    // IXN DATA:
-    // LEN: 12 bytes
-    // [0-8]: Anchor Ixn Discriminator
-    // [9-12]: Random Result as u32
-    let mut ixn_data = get_ixn_discriminator("trigger_update").to_vec();
-    ixn_data.append(&mut random_bytes);
-
-    // ACCOUNTS:
-    // 1. Global (mut): global state
-    // 4. Enclave Signer (signer): our Gramine generated keypair
-    // 2. Switchboard Function
-    // 3. Switchboard Function Request
-    let request_pubkey = runner.function_request_key.unwrap();
-
-    Ok(vec![Instruction {
-        program_id: data.program_id,
-        data: ixn_data,
-        accounts: vec![
-            AccountMeta::new(data.global, false), // Global
-            AccountMeta::new(data.mint, false), // Mint
-            AccountMeta::new_readonly(runner.signer, true), // Enclave signer
-            AccountMeta::new_readonly(runner.switchboard, false), // Switchboard
-            AccountMeta::new_readonly(runner.switchboard_state, false), // Switchboard_state
-            AccountMeta::new_readonly(runner.attestation_queue.unwrap(), false), // Switchboard_attestation_queue
-            AccountMeta::new_readonly(runner.function, false), // switchboard_function
-            AccountMeta::new_readonly(request_pubkey.clone(), false), // Switchboard_request
-            AccountMeta::new(data.sb_escrow, false), // switchboard_request_escrow 
-            AccountMeta::new_readonly(anchor_spl::token::spl_token::native_mint::ID, false), // Switchboard_mint == Native SOL
-            AccountMeta::new_readonly(anchor_spl::token::ID, false), // TID
-            AccountMeta::new_readonly(anchor_spl::token_2022::ID, false), // TID_22
-            AccountMeta::new_readonly(anchor_spl::associated_token::ID, false), // ATID
-            AccountMeta::new_readonly(anchor_lang::system_program::ID, false), // SID
-        ],
-    }])
-
-
-    // Ok(vec![Instruction {
-    //     program_id: data.program_id,
-    //     data: ixn_data,
-    //     accounts: vec![
-    //         AccountMeta::new(data.global, false), // Global
-    //         AccountMeta::new(data.mint, false), // Mint
-    //         AccountMeta::new_readonly(runner.signer, true), // Enclave signer
-    //         AccountMeta::new_readonly(runner.switchboard, false), // Switchboard
-    //         AccountMeta::new_readonly(runner.switchboard_state, false), // Switchboard_state
-    //         AccountMeta::new_readonly(runner.attestation_queue.unwrap(), false), // Switchboard_attestation_queue
-    //         AccountMeta::new_readonly(runner.function, false), // switchboard_function
-    //         AccountMeta::new(runner.function_request_key.unwrap().clone(), false), // Switchboard_request
-    //         AccountMeta::new_readonly(request_pubkey.clone(), false), // switchboard_request_escrow 
-    //         AccountMeta::new_readonly(anchor_spl::token::spl_token::native_mint::ID, false), // Switchboard_mint == Native SOL
-    //         AccountMeta::new_readonly(anchor_spl::token::ID, false), // TID
-    //         AccountMeta::new_readonly(anchor_spl::token_2022::ID, false), // TID_22
-    //         AccountMeta::new_readonly(anchor_spl::associated_token::ID, false), // ATID
-    //         AccountMeta::new_readonly(anchor_lang::system_program::ID, false), // SID
-    //     ],
-    // }])
+    let data: ContainerParams = decode_params(&params).map_err(|err| {
+        println!("failed to decode params: {:?}", err);
+        Error::ArgParseFail
+    })?;
+    // Generate our random buffer
+    let randomness = generate_random_bytes(&mut GramineRandomness);
+    println!("randomness is: {:?}", randomness);
+
+    // Account order and ixn data layout come from the l2 program itself
+    let switchboard = SwitchboardAccounts {
+        attestation_queue: runner.attestation_queue.unwrap(),
+        function: runner.function,
+        request: runner.function_request_key.unwrap(),
+    };
+    if !params_match_request(&data, &switchboard) {
+        println!("container params do not match the request: {:?}", data);
+        return Err(Error::ParamsMismatch.into());
+    }
+    let params = TriggerUpdateParams {
+        version: l2_client::l2::TRIGGER_UPDATE_VERSION,
+        randomness,
+    };
+
+    Ok(vec![l2_client::trigger_update(
+        &data.mint,
+        &runner.signer,
+        &switchboard,
+        params,
+    )])
 }
 
 #[sb_error]
 pub enum Error {
     ArgParseFail,
+    ParamsMismatch,
+}
+
+// The request was created by init_global for this program, mint manager and escrow
+fn params_match_request(data: &ContainerParams, switchboard: &SwitchboardAccounts) -> bool {
+    data.program_id == l2_client::l2::ID
+        && data.mint_manager == l2_client::mint_manager(&data.mint).0
+        && data.escrow == l2_client::switchboard_request_escrow(&switchboard.request)
+}
+
+// Where the random bytes come from, injectable so tests run outside an enclave
+pub trait RandomnessSource {
+    fn fill(&mut self, bytes: &mut [u8]);
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+}
+
+pub struct GramineRandomness;
+
+impl RandomnessSource for GramineRandomness {
+    fn fill(&mut self, bytes: &mut [u8]) {
+        Gramine::read_rand(bytes).expect("gramine failed to generate randomness");
+    }
 }
 
-fn generate_randomness(min: u8, max: u8) -> u8 {
+// 4 independent u64 draws, the program derives the fee and interval from disjoint bytes
+fn generate_random_bytes<R: RandomnessSource>(rng: &mut R) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for chunk in bytes.chunks_exact_mut(8) {
+        chunk.copy_from_slice(&generate_randomness(rng, 0, u64::MAX).to_le_bytes());
+    }
+    bytes
+}
+
+// Uniform in [min, max], rejection sampled so there is no modulo bias
+fn generate_randomness<R: RandomnessSource>(rng: &mut R, min: u64, max: u64) -> u64 {
     if min == max {
         return min;
     }
     if min > max {
-        return generate_randomness(max, min);
+        return generate_randomness(rng, max, min);
     }
 
-    // We add one so its inclusive [min, max]
-    let window = (max + 1) - min;
-
-    let mut bytes: [u8; 4] = [0u8; 4];
-    Gramine::read_rand(&mut bytes).expect("gramine failed to generate randomness");
-    let raw_result: &[u8] = bytemuck::cast_slice(&bytes[..]);
+    let span = max - min;
+    if span == u64::MAX {
+        return rng.next_u64();
+    }
 
-    (raw_result[0] % window) + min
+    // We add one so its inclusive [min, max]
+    let window = span + 1;
+    // Largest value before the last, incomplete window
+    let zone = u64::MAX - (u64::MAX - window + 1) % window;
+    loop {
+        let raw_result = rng.next_u64();
+        if raw_result <= zone {
+            return (raw_result % window) + min;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // Deterministic xorshift, good enough for distribution checks
+    struct TestRandomness(u64);
+
+    impl RandomnessSource for TestRandomness {
+        fn fill(&mut self, bytes: &mut [u8]) {
+            for byte in bytes.iter_mut() {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                *byte = self.0 as u8;
+            }
+        }
+    }
+
+    // Replays the given u64s in order
+    struct FixedRandomness(Vec<u64>);
+
+    impl RandomnessSource for FixedRandomness {
+        fn fill(&mut self, _bytes: &mut [u8]) {
+            unreachable!()
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0.remove(0)
+        }
+    }
+
     // 1. Check when lower_bound is greater than upper_bound
     #[test]
     fn test_generate_randomness_with_flipped_bounds() {
         let min = 100;
         let max = 50;
 
-        let result = generate_randomness(100, 50);
-        assert!(result >= max && result < min);
+        let result = generate_randomness(&mut TestRandomness(42), min, max);
+        assert!(result >= max && result <= min);
     }
 
     // 2. Check when lower_bound is equal to upper_bound
     #[test]
     fn test_generate_randomness_with_equal_bounds() {
         let bound = 100;
-        assert_eq!(generate_randomness(bound, bound), bound);
+        assert_eq!(generate_randomness(&mut TestRandomness(42), bound, bound), bound);
     }
 
     // 3. Test within a range
@@ -126,27 +161,91 @@ mod tests {
         let min = 100;
         let max = 200;
 
-        let result = generate_randomness(min, max);
-
-        assert!(result >= min && result < max);
+        let mut rng = TestRandomness(42);
+        for _ in 0..1000 {
+            let result = generate_randomness(&mut rng, min, max);
+            assert!(result >= min && result <= max);
+        }
     }
 
-    // 4. Test randomness distribution (not truly deterministic, but a sanity check)
+    // 4. Test randomness distribution
     #[test]
     fn test_generate_randomness_distribution() {
         let min = 0;
         let max = 9;
 
+        let mut rng = TestRandomness(42);
         let mut counts = vec![0; 10];
-        for _ in 0..1000 {
-            let result = generate_randomness(min, max);
+        for _ in 0..10_000 {
+            let result = generate_randomness(&mut rng, min, max);
             let index: usize = result as usize;
             counts[index] += 1;
         }
 
-        // Ensure all counts are non-zero (probabilistically should be the case)
+        // Each bucket expects 1000, allow a generous margin
         for count in counts.iter() {
-            assert!(*count > 0);
+            assert!(*count > 800 && *count < 1200);
+        }
+    }
+
+    // 5. Values in the incomplete last window are rejected
+    #[test]
+    fn test_generate_randomness_rejects_biased_values() {
+        // 2^64 % 10 == 6, so the top 6 values are rejected
+        let mut rng = FixedRandomness(vec![u64::MAX, u64::MAX - 5, 13]);
+        assert_eq!(generate_randomness(&mut rng, 0, 9), 3);
+
+        let mut rng = FixedRandomness(vec![u64::MAX - 6]);
+        assert_eq!(generate_randomness(&mut rng, 0, 9), (u64::MAX - 6) % 10);
+    }
+
+    // 6. Full u64 range takes the raw value
+    #[test]
+    fn test_generate_randomness_full_range() {
+        let mut rng = FixedRandomness(vec![u64::MAX]);
+        assert_eq!(generate_randomness(&mut rng, 0, u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_params_match_request() {
+        let mint = Pubkey::new_unique();
+        let switchboard = SwitchboardAccounts {
+            attestation_queue: Pubkey::new_unique(),
+            function: Pubkey::new_unique(),
+            request: Pubkey::new_unique(),
+        };
+        let data = ContainerParams {
+            program_id: l2_client::l2::ID,
+            mint_manager: l2_client::mint_manager(&mint).0,
+            mint,
+            escrow: l2_client::switchboard_request_escrow(&switchboard.request),
+        };
+        assert!(params_match_request(&data, &switchboard));
+
+        let other = Pubkey::new_unique();
+        let mismatches = [
+            ContainerParams {
+                program_id: other,
+                ..data.clone()
+            },
+            ContainerParams {
+                mint_manager: other,
+                ..data.clone()
+            },
+            ContainerParams {
+                escrow: other,
+                ..data
+            },
+        ];
+        for data in mismatches {
+            assert!(!params_match_request(&data, &switchboard));
         }
     }
+
+    #[test]
+    fn test_generate_random_bytes_is_deterministic() {
+        let bytes = generate_random_bytes(&mut TestRandomness(42));
+        assert_eq!(bytes, generate_random_bytes(&mut TestRandomness(42)));
+        assert_ne!(bytes, generate_random_bytes(&mut TestRandomness(7)));
+    }
 }